@@ -0,0 +1,21 @@
+use cosmwasm_schema::{export_schema, remove_schemas};
+use schemars::schema_for;
+use std::env::current_dir;
+use std::fs::create_dir_all;
+
+use ysip::factory::{ConfigResponse, ExecuteMsg, InstantiateMsg, PairsResponse, QueryMsg};
+use ysip::pair::PairInfo;
+
+fn main() {
+    let mut out_dir = current_dir().unwrap();
+    out_dir.push("contracts/factory/schema");
+    create_dir_all(&out_dir).unwrap();
+    remove_schemas(&out_dir).unwrap();
+
+    export_schema(&schema_for!(InstantiateMsg), &out_dir);
+    export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(PairInfo), &out_dir);
+    export_schema(&schema_for!(PairsResponse), &out_dir);
+}
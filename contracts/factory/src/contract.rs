@@ -0,0 +1,372 @@
+use crate::error::ContractError;
+use crate::state::{Config, CONFIG, PAIRS, TMP_PAIR_KEY};
+use cosmwasm_std::{
+    entry_point, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Reply,
+    ReplyOn, Response, StdError, StdResult, SubMsg, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw_storage_plus::Bound;
+use std::str::FromStr;
+use ysip::asset::{pair_key, AssetInfo};
+use ysip::factory::{ConfigResponse, ExecuteMsg, InstantiateMsg, PairsResponse, QueryMsg};
+use ysip::pair::{
+    InstantiateMsg as PairInstantiateMsg, PairInfo, PairInfoResponse, PairType,
+    QueryMsg as PairQueryMsg,
+};
+
+const CONTRACT_NAME: &str = "ysip-factory-contract";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const INSTANTIATE_PAIR_REPLY_ID: u64 = 1;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // fee config is passed as is to the pairs, so it has to be validated here
+    Decimal::from_str(&msg.protocol_fee_percent)?;
+    Decimal::from_str(&msg.lp_fee_percent)?;
+
+    let config = Config {
+        owner: info.sender,
+        pair_code_id: msg.pair_code_id,
+        token_code_id: msg.token_code_id,
+        protocol_fee_recipient: deps.api.addr_validate(&msg.protocol_fee_recipient)?,
+        protocol_fee_percent: msg.protocol_fee_percent,
+        lp_fee_percent: msg.lp_fee_percent,
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "instantiate"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::CreatePair {
+            asset_infos,
+            pair_type,
+            amp,
+        } => execute_create_pair(deps, env, info, asset_infos, pair_type, amp),
+    }
+}
+
+fn execute_create_pair(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    pair_type: Option<PairType>,
+    amp: Option<u64>,
+) -> Result<Response, ContractError> {
+    asset_infos[0].check_is_valid(deps.api)?;
+    asset_infos[1].check_is_valid(deps.api)?;
+
+    if asset_infos[0] == asset_infos[1] {
+        return Err(ContractError::OverlappingAssets {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let pair_type = pair_type.unwrap_or(PairType::ConstantProduct {});
+
+    // there is a single pair per asset pair, so anyone could occupy the slot with a stable curve
+    if pair_type == (PairType::Stable {}) && info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let key = pair_key(&asset_infos);
+
+    if PAIRS.has(deps.storage, &key) {
+        return Err(ContractError::PairAlreadyExists {});
+    }
+
+    TMP_PAIR_KEY.save(deps.storage, &key)?;
+
+    let sub_msg = SubMsg {
+        id: INSTANTIATE_PAIR_REPLY_ID,
+        msg: WasmMsg::Instantiate {
+            admin: Some(config.owner.to_string()),
+            code_id: config.pair_code_id,
+            msg: to_binary(&PairInstantiateMsg {
+                asset_infos: asset_infos.clone(),
+                token_code_id: config.token_code_id,
+                protocol_fee_recipient: config.protocol_fee_recipient.to_string(),
+                protocol_fee_percent: config.protocol_fee_percent,
+                lp_fee_percent: config.lp_fee_percent,
+                owner: Some(config.owner.to_string()),
+                pair_type,
+                amp,
+            })?,
+            funds: vec![],
+            label: "YSIP pair".to_string(),
+        }
+        .into(),
+        gas_limit: None,
+        reply_on: ReplyOn::Success,
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "create_pair")
+        .add_attribute("pair", format!("{}-{}", asset_infos[0], asset_infos[1]))
+        .add_submessage(sub_msg))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_PAIR_REPLY_ID => {
+            let key = TMP_PAIR_KEY.load(deps.storage)?;
+            TMP_PAIR_KEY.remove(deps.storage);
+
+            let res = cw_utils::parse_reply_instantiate_data(msg)
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+            let pair_contract = deps.api.addr_validate(&res.contract_address)?;
+
+            PAIRS.save(deps.storage, &key, &pair_contract)?;
+
+            Ok(Response::new().add_attribute("pair_contract_addr", pair_contract))
+        }
+        _ => Err(ContractError::NotFound {}),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => query_config(deps),
+        QueryMsg::Pair { asset_infos } => to_binary(&query_pair(deps, asset_infos)?),
+        QueryMsg::Pairs { start_after, limit } => query_pairs(deps, start_after, limit),
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+
+    to_binary(&ConfigResponse {
+        owner: config.owner.to_string(),
+        pair_code_id: config.pair_code_id,
+        token_code_id: config.token_code_id,
+        protocol_fee_recipient: config.protocol_fee_recipient.to_string(),
+        protocol_fee_percent: config.protocol_fee_percent,
+        lp_fee_percent: config.lp_fee_percent,
+    })
+}
+
+fn query_pair_info(deps: Deps, pair_contract: &Addr) -> StdResult<PairInfo> {
+    let res: PairInfoResponse = deps
+        .querier
+        .query_wasm_smart(pair_contract, &PairQueryMsg::PairInfo {})?;
+
+    Ok(PairInfo {
+        asset_infos: res.assets,
+        contract_addr: res.contract_addr,
+        liquidity_token: res.liquidity_token,
+        pair_type: res.pair_type,
+    })
+}
+
+fn query_pair(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<PairInfo> {
+    let pair_contract = PAIRS.load(deps.storage, &pair_key(&asset_infos))?;
+    query_pair_info(deps, &pair_contract)
+}
+
+fn query_pairs(
+    deps: Deps,
+    start_after: Option<[AssetInfo; 2]>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_key = start_after.map(|asset_infos| pair_key(&asset_infos));
+    let start = start_key.as_deref().map(Bound::exclusive);
+
+    let pairs = PAIRS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, pair_contract) = item?;
+            query_pair_info(deps, &pair_contract)
+        })
+        .collect::<StdResult<Vec<PairInfo>>>()?;
+
+    to_binary(&PairsResponse { pairs })
+}
+
+#[cfg(test)]
+mod test_factory {
+    use crate::contract::{execute, instantiate, query, reply, MAX_LIMIT};
+    use crate::error::ContractError;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        from_binary, to_binary, Addr, Binary, ContractResult, OwnedDeps, Reply, SubMsgResponse,
+        SubMsgResult, SystemResult, WasmQuery,
+    };
+    use std::collections::HashMap;
+    use ysip::asset::{pair_key, AssetInfo};
+    use ysip::factory::{ExecuteMsg, InstantiateMsg, PairsResponse, QueryMsg};
+    use ysip::pair::{PairInfo, PairInfoResponse, PairType};
+
+    const OWNER: &str = "owner";
+
+    type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+    fn native(denom: &str) -> AssetInfo {
+        AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        }
+    }
+
+    fn instantiate_factory(deps: &mut MockDeps) {
+        let msg = InstantiateMsg {
+            pair_code_id: 1,
+            token_code_id: 2,
+            protocol_fee_recipient: OWNER.to_string(),
+            protocol_fee_percent: "0.05".to_string(),
+            lp_fee_percent: "0.25".to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    }
+
+    fn create_pair(deps: &mut MockDeps, asset_infos: [AssetInfo; 2]) -> Result<(), ContractError> {
+        let msg = ExecuteMsg::CreatePair {
+            asset_infos,
+            pair_type: None,
+            amp: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg)?;
+        Ok(())
+    }
+
+    /// Replies to the pair instantiation with `MsgInstantiateContractResponse { contract_address }`
+    fn reply_instantiate(deps: &mut MockDeps, contract_addr: &str) {
+        let mut data = vec![0x0a, contract_addr.len() as u8];
+        data.extend_from_slice(contract_addr.as_bytes());
+
+        let msg = Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary(data)),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), msg).unwrap();
+    }
+
+    /// Answers the `PairInfo` query of the pairs created in the test
+    fn mock_pairs(deps: &mut MockDeps, pairs: HashMap<String, [AssetInfo; 2]>) {
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, .. } => {
+                let res = PairInfoResponse {
+                    assets: pairs[contract_addr].clone(),
+                    contract_addr: Addr::unchecked(contract_addr),
+                    liquidity_token: Addr::unchecked("lp"),
+                    pair_type: PairType::ConstantProduct {},
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+            }
+            _ => unimplemented!(),
+        });
+    }
+
+    fn query_pairs(
+        deps: &MockDeps,
+        start_after: Option<[AssetInfo; 2]>,
+        limit: Option<u32>,
+    ) -> Vec<PairInfo> {
+        let msg = QueryMsg::Pairs { start_after, limit };
+        let res: PairsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.pairs
+    }
+
+    #[test]
+    fn test_duplicate_pair() {
+        let mut deps = mock_dependencies();
+        instantiate_factory(&mut deps);
+
+        let asset_infos = [
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("token"),
+            },
+            native("ukrw"),
+        ];
+        create_pair(&mut deps, asset_infos.clone()).unwrap();
+        reply_instantiate(&mut deps, "pair");
+
+        // the same pair in either order
+        let reversed = [asset_infos[1].clone(), asset_infos[0].clone()];
+        for asset_infos in [asset_infos, reversed] {
+            assert_eq!(
+                create_pair(&mut deps, asset_infos).unwrap_err(),
+                ContractError::PairAlreadyExists {}
+            );
+        }
+
+        // a token and a native denom with the same name are different assets
+        create_pair(&mut deps, [native("token"), native("ukrw")]).unwrap();
+    }
+
+    #[test]
+    fn test_pairs_pagination() {
+        let mut deps = mock_dependencies();
+        instantiate_factory(&mut deps);
+
+        let mut pairs = HashMap::new();
+        for i in 0..35 {
+            let asset_infos = [native(&format!("denom{:02}", i)), native("ukrw")];
+            let contract_addr = format!("pair{:02}", i);
+
+            create_pair(&mut deps, asset_infos.clone()).unwrap();
+            reply_instantiate(&mut deps, &contract_addr);
+            pairs.insert(contract_addr, asset_infos);
+        }
+
+        // pairs are listed in the order of their keys
+        let mut expected: Vec<_> = pairs
+            .iter()
+            .map(|(contract_addr, asset_infos)| (pair_key(asset_infos), contract_addr.clone()))
+            .collect();
+        expected.sort();
+        let expected: Vec<String> = expected.into_iter().map(|(_, addr)| addr).collect();
+        let last = pairs[expected.last().unwrap()].clone();
+        mock_pairs(&mut deps, pairs);
+
+        let addrs = |pairs: Vec<PairInfo>| -> Vec<String> {
+            pairs
+                .into_iter()
+                .map(|pair| pair.contract_addr.to_string())
+                .collect()
+        };
+
+        // default limit
+        let page = query_pairs(&deps, None, None);
+        assert_eq!(addrs(page.clone()), expected[..10]);
+
+        // the next page starts after the last pair of the previous one
+        let start_after = page.last().unwrap().asset_infos.clone();
+        let page = query_pairs(&deps, Some(start_after), Some(5));
+        assert_eq!(addrs(page), expected[10..15]);
+
+        // the limit is capped
+        let page = query_pairs(&deps, None, Some(100));
+        assert_eq!(page.len(), MAX_LIMIT as usize);
+        assert_eq!(addrs(page), expected[..MAX_LIMIT as usize]);
+
+        // nothing after the last pair
+        assert!(query_pairs(&deps, Some(last), None).is_empty());
+    }
+}
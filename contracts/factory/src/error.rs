@@ -0,0 +1,20 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Overlapping assets in asset infos")]
+    OverlappingAssets {},
+
+    #[error("Pair already exists")]
+    PairAlreadyExists {},
+
+    #[error("Not Found")]
+    NotFound {},
+}
@@ -0,0 +1,23 @@
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: Addr,
+    /// code_id for pair contract
+    pub pair_code_id: u64,
+    /// code_id for the LP token of every pair
+    pub token_code_id: u64,
+    /// default fee config passed to every created pair
+    pub protocol_fee_recipient: Addr,
+    pub protocol_fee_percent: String,
+    pub lp_fee_percent: String,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+/// pair contract address by `ysip::asset::pair_key`
+pub const PAIRS: Map<&[u8], Addr> = Map::new("pairs");
+/// key of the pair being instantiated, consumed by the reply
+pub const TMP_PAIR_KEY: Item<Vec<u8>> = Item::new("tmp_pair_key");
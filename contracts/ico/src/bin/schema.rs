@@ -1,7 +1,8 @@
 use cosmwasm_schema::{export_schema, remove_schemas};
 use ico::msg::{
-    ExecuteMsg, FundingAmountResponse, InstantiateMsg, IsFundingFinishedResponse,
-    PairAddressResponse, QueryMsg, TokenAddressResponse,
+    Cw20HookMsg, ExecuteMsg, FundingAmountResponse, InstantiateMsg, IsFundingFinishedResponse,
+    IsWhitelistedResponse, PairAddressResponse, PendingRevenueResponse, QueryMsg,
+    RevenueHistoryResponse, TokenAddressResponse, VestingInfoResponse,
 };
 use schemars::schema_for;
 use std::env::current_dir;
@@ -15,9 +16,14 @@ fn main() {
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(FundingAmountResponse), &out_dir);
     export_schema(&schema_for!(IsFundingFinishedResponse), &out_dir);
     export_schema(&schema_for!(TokenAddressResponse), &out_dir);
     export_schema(&schema_for!(PairAddressResponse), &out_dir);
+    export_schema(&schema_for!(VestingInfoResponse), &out_dir);
+    export_schema(&schema_for!(IsWhitelistedResponse), &out_dir);
+    export_schema(&schema_for!(PendingRevenueResponse), &out_dir);
+    export_schema(&schema_for!(RevenueHistoryResponse), &out_dir);
 }
@@ -1,54 +1,141 @@
 use crate::error::ContractError;
 use crate::{execute, query};
 use cosmwasm_std::{
-    entry_point, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, Reply,
-    Response, StdResult, Uint128, WasmMsg,
+    coin, entry_point, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env,
+    MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
 use cw20::Cw20ExecuteMsg;
+use cw_utils::{Expiration, Scheduled};
+use std::str::FromStr;
+use token::msg::ExecuteMsg as TokenExecuteMsg;
+use ysip::asset::{Asset, AssetInfo};
+use ysip::pair::{
+    ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg, PairInfoResponse, PairType,
+    QueryMsg as PairQueryMsg,
+};
+use ysip::querier::query_token_balance;
 use ysip::utils::get_cw20_transfer_msg;
 
 const CONTRACT_NAME: &str = "ysip-ico-contract";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub const END_FUNDING_REPLAY_ID: u64 = 1;
+pub const INSTANTIATE_PAIR_REPLY_ID: u64 = 2;
+pub const PROVIDE_LIQUIDITY_REPLY_ID: u64 = 3;
 
 use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Config, CONFIG, FUNDING};
+use crate::state::{Config, PoolConfig, VestingStart, CONFIG, WHITELIST};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     let recipient_addr = deps.api.addr_validate(&msg.recipient)?;
 
+    if let Some(vesting) = &msg.vesting {
+        if !vesting.is_valid() {
+            return Err(ContractError::InvalidVestingSchedule {});
+        }
+    }
+
+    // funding has to end at some point for funders to get either tokens or refund
+    if matches!(msg.deadline, Expiration::Never {}) || msg.deadline.is_expired(&env.block) {
+        return Err(ContractError::InvalidDeadline {});
+    }
+
+    if let Some(start) = msg.start {
+        let starts_after_deadline = match (start, msg.deadline) {
+            (Scheduled::AtHeight(start), Expiration::AtHeight(deadline)) => start >= deadline,
+            (Scheduled::AtTime(start), Expiration::AtTime(deadline)) => start >= deadline,
+            _ => false,
+        };
+        if starts_after_deadline {
+            return Err(ContractError::InvalidDeadline {});
+        }
+    }
+
+    msg.funding_asset.check_is_valid(deps.api)?;
+
+    if msg.soft_cap.is_zero() || msg.soft_cap > msg.hard_cap {
+        return Err(ContractError::InvalidFundingCap {});
+    }
+
+    if let (Some(min_contribution), Some(max_contribution)) =
+        (msg.min_contribution, msg.max_contribution_per_address)
+    {
+        if min_contribution > max_contribution {
+            return Err(ContractError::InvalidContributionLimits {});
+        }
+    }
+
+    let pool = match msg.pool {
+        Some(pool) => {
+            if pool.funding_ratio > Decimal::one() || pool.channel_token_amount.is_zero() {
+                return Err(ContractError::InvalidPoolConfig {});
+            }
+
+            // fee config is passed as is to the pair, so it has to be validated here
+            Decimal::from_str(&pool.protocol_fee_percent)?;
+            Decimal::from_str(&pool.lp_fee_percent)?;
+
+            Some(PoolConfig {
+                pair_code_id: pool.pair_code_id,
+                funding_ratio: pool.funding_ratio,
+                channel_token_amount: pool.channel_token_amount,
+                protocol_fee_recipient: deps.api.addr_validate(&pool.protocol_fee_recipient)?,
+                protocol_fee_percent: pool.protocol_fee_percent,
+                lp_fee_percent: pool.lp_fee_percent,
+                treasury: pool
+                    .treasury
+                    .map(|treasury| deps.api.addr_validate(&treasury))
+                    .transpose()?,
+            })
+        }
+        None => None,
+    };
+
     let config = Config {
         admin: info.sender,
         token_code_id: msg.token_code_id,
         token_name: msg.token_name.to_string(),
         token_symbol: msg.token_symbol.to_string(),
-        target_funding_amount: msg.target_funding,
+        soft_cap: msg.soft_cap,
+        hard_cap: msg.hard_cap,
+        funding_asset: msg.funding_asset,
         current_funding_amount: Uint128::zero(),
         channel_token_amount: msg.channel_token_amount,
+        start: msg.start,
         deadline: msg.deadline,
-        /// token_contract would be replace with the reply msg
+        // token_contract would be replace with the reply msg
         token_contract: Addr::unchecked(""),
-        /// pair_contract would be replace with the reply msg
+        // pair_contract would be replace with the reply msg
         pair_contract: Addr::unchecked(""),
         recipient: recipient_addr,
         finished: false,
         is_token_distributed: false,
+        pool,
+        vesting: msg.vesting,
+        vesting_start: None,
+        whitelist_enabled: msg.whitelist.is_some(),
+        min_contribution: msg.min_contribution,
+        max_contribution_per_address: msg.max_contribution_per_address,
     };
 
     CONFIG.save(deps.storage, &config)?;
 
+    for addr in msg.whitelist.unwrap_or_default() {
+        WHITELIST.save(deps.storage, deps.api.addr_validate(&addr)?, &Empty {})?;
+    }
+
     Ok(Response::new()
         .add_attribute("action", "instantiate")
-        .add_attribute("target_funding", msg.target_funding))
+        .add_attribute("soft_cap", msg.soft_cap)
+        .add_attribute("hard_cap", msg.hard_cap))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -59,11 +146,54 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::FundChannelToken {} => execute::fund_channel_token(deps, env, info),
+        ExecuteMsg::Receive(msg) => execute::receive_cw20(deps, env, info, msg),
+        ExecuteMsg::FundChannelToken {} => {
+            let amount = execute::received_native_amount(deps.as_ref(), &info)?;
+            execute::fund_channel_token(deps, env, info.sender, amount)
+        }
         ExecuteMsg::EndFunding {} => execute::end_funding(deps, env, info),
         ExecuteMsg::Refund {} => execute::refund(deps, env, info),
         ExecuteMsg::TransferFund { amount } => execute::transfer_fund(deps, env, info, amount),
-        ExecuteMsg::Allocation { amount } => execute::allocation(deps, info, amount),
+        ExecuteMsg::Allocation { amount } => {
+            if execute::received_native_amount(deps.as_ref(), &info)? != amount {
+                return Err(ContractError::InvalidCoinAmount {});
+            }
+            execute::allocation(deps, env, amount)
+        }
+        ExecuteMsg::ClaimRevenue {} => execute::claim_revenue(deps, info),
+        ExecuteMsg::BalanceChanged {
+            address,
+            new_balance,
+            ..
+        } => execute::balance_changed(deps, env, info, address, new_balance),
+        ExecuteMsg::ClaimVested {} => execute::claim_vested(deps, env, info),
+        ExecuteMsg::ClaimTokens {} => execute::claim_tokens(deps, env, info),
+        ExecuteMsg::DistributeBatch { limit } => execute::distribute_batch(deps, env, info, limit),
+        ExecuteMsg::UpdateWhitelist { add, remove } => {
+            execute::update_whitelist(deps, info, add, remove)
+        }
+        ExecuteMsg::UpdateTransferPolicy {
+            transfers_enabled_at,
+        } => execute::update_channel_token(
+            deps,
+            info,
+            TokenExecuteMsg::UpdateTransferPolicy {
+                transfers_enabled_at,
+            },
+        ),
+        ExecuteMsg::LockAddress { address, unlock_at } => execute::update_channel_token(
+            deps,
+            info,
+            TokenExecuteMsg::LockAddress { address, unlock_at },
+        ),
+        ExecuteMsg::UnlockAddress { address } => {
+            execute::update_channel_token(deps, info, TokenExecuteMsg::UnlockAddress { address })
+        }
+        ExecuteMsg::UpdateExemptions { add, remove } => execute::update_channel_token(
+            deps,
+            info,
+            TokenExecuteMsg::UpdateExemptions { add, remove },
+        ),
     }
 }
 
@@ -75,56 +205,246 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::TotalFundingAmount {} => query::total_funding_amount(deps),
         QueryMsg::IsFundingFinished {} => query::funding_finished(deps, env),
         QueryMsg::TokenAddress {} => query::token_address(deps),
+        QueryMsg::PairAddress {} => query::pair_address(deps),
+        QueryMsg::VestingInfo { addr } => query::vesting_info(deps, env, &addr),
+        QueryMsg::IsWhitelisted { addr } => query::is_whitelisted(deps, &addr),
+        QueryMsg::PendingRevenue { addr } => query::pending_revenue(deps, &addr),
+        QueryMsg::RevenueHistory { start_after, limit } => {
+            query::revenue_history(deps, start_after, limit)
+        }
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        END_FUNDING_REPLAY_ID => reply_end_funding(deps, env, msg),
+        INSTANTIATE_PAIR_REPLY_ID => reply_instantiate_pair(deps, env, msg),
+        PROVIDE_LIQUIDITY_REPLY_ID => reply_provide_liquidity(deps, env),
+        _ => Err(ContractError::NotFound {}),
+    }
+}
+
+fn reply_end_funding(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
-    let res = cw_utils::parse_reply_instantiate_data(msg.clone()).unwrap();
+    let res = cw_utils::parse_reply_instantiate_data(msg).map_err(|e| ContractError::Generic {
+        inner: e.to_string(),
+    })?;
 
-    match msg.id {
-        END_FUNDING_REPLAY_ID => {
-            let mint_msg = CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: res.contract_address.clone(),
-                msg: to_binary(&Cw20ExecuteMsg::Mint {
-                    recipient: env.contract.address.to_string(),
-                    amount: config.channel_token_amount,
+    let pool_channel_token_amount = config
+        .pool
+        .as_ref()
+        .map(|pool| pool.channel_token_amount)
+        .unwrap_or_default();
+
+    let mint_amount = config
+        .channel_token_amount
+        .checked_add(pool_channel_token_amount)
+        .map_err(StdError::overflow)?;
+
+    // the channel token notifies the ico of balance changes to track revenue of holders
+    let add_hook_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: res.contract_address.clone(),
+        msg: to_binary(&TokenExecuteMsg::AddHook {
+            addr: env.contract.address.to_string(),
+        })?,
+        funds: vec![],
+    });
+
+    // tokens paid out by the ico and the treasury keep moving under a restrictive policy
+    let mut exemptions = vec![env.contract.address.to_string()];
+    if let Some(treasury) = config.pool.as_ref().and_then(|pool| pool.treasury.as_ref()) {
+        exemptions.push(treasury.to_string());
+    }
+    let update_exemptions_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: res.contract_address.clone(),
+        msg: to_binary(&TokenExecuteMsg::UpdateExemptions {
+            add: exemptions,
+            remove: vec![],
+        })?,
+        funds: vec![],
+    });
+
+    let mint_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: res.contract_address.clone(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: env.contract.address.to_string(),
+            amount: mint_amount,
+        })?,
+        funds: vec![],
+    });
+
+    // purchased tokens stay in the ico contract until funders claim them
+    if config.vesting.is_some() {
+        config.vesting_start = Some(VestingStart {
+            height: env.block.height,
+            time: env.block.time.seconds(),
+        });
+    }
+
+    config.token_contract = Addr::unchecked(res.contract_address.clone());
+    CONFIG.save(deps.storage, &config)?;
+
+    let mut sub_msgs: Vec<SubMsg> = vec![];
+
+    if let Some(pool) = &config.pool {
+        sub_msgs.push(SubMsg {
+            id: INSTANTIATE_PAIR_REPLY_ID,
+            msg: WasmMsg::Instantiate {
+                admin: Some(config.admin.to_string()),
+                code_id: pool.pair_code_id,
+                msg: to_binary(&PairInstantiateMsg {
+                    asset_infos: [
+                        AssetInfo::Token {
+                            contract_addr: config.token_contract.clone(),
+                        },
+                        config.funding_asset.clone(),
+                    ],
+                    token_code_id: config.token_code_id,
+                    protocol_fee_recipient: pool.protocol_fee_recipient.to_string(),
+                    protocol_fee_percent: pool.protocol_fee_percent.clone(),
+                    lp_fee_percent: pool.lp_fee_percent.clone(),
+                    owner: Some(config.admin.to_string()),
+                    pair_type: PairType::ConstantProduct {},
+                    amp: None,
                 })?,
                 funds: vec![],
-            });
-
-            let funding: StdResult<Vec<(Addr, Uint128)>> = FUNDING
-                .range(deps.storage, None, None, Order::Ascending)
-                .collect();
-
-            let transfer_ico_tokens_msgs = funding
-                .map_err(|_| ContractError::NotFound {})?
-                .iter()
-                .map(|(addr, amount)| -> Result<CosmosMsg, ContractError> {
-                    Ok(get_cw20_transfer_msg(
-                        addr,
-                        &Addr::unchecked(&res.contract_address),
-                        config
-                            .channel_token_amount
-                            .checked_multiply_ratio(*amount, config.current_funding_amount)
-                            .map_err(|e| ContractError::Generic {
-                                inner: format!("{:?}", e),
-                            })?,
-                    )
-                    .map_err(|_| ContractError::NotFound {})?)
-                })
-                .collect::<Result<Vec<CosmosMsg>, ContractError>>();
-
-            config.token_contract = Addr::unchecked(res.contract_address.clone());
-            CONFIG.save(deps.storage, &config)?;
-
-            Ok(Response::new()
-                .add_attribute("channel_token_instantiate", res.clone().contract_address)
-                .add_attribute("channel_token_mint", config.channel_token_amount)
-                .add_message(mint_msg)
-                .add_messages(transfer_ico_tokens_msgs?))
-        }
-        _ => Err(ContractError::NotFound {}),
+                label: format!("{} channel token pair", config.token_name),
+            }
+            .into(),
+            gas_limit: None,
+            reply_on: ReplyOn::Success,
+        });
     }
+
+    Ok(Response::new()
+        .add_attribute("channel_token_instantiate", res.contract_address)
+        .add_attribute("channel_token_mint", mint_amount)
+        .add_message(add_hook_msg)
+        .add_message(update_exemptions_msg)
+        .add_message(mint_msg)
+        .add_submessages(sub_msgs))
+}
+
+fn reply_instantiate_pair(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let pool = config.pool.clone().ok_or(ContractError::NotFound {})?;
+    let res = cw_utils::parse_reply_instantiate_data(msg).map_err(|e| ContractError::Generic {
+        inner: e.to_string(),
+    })?;
+
+    config.pair_contract = deps.api.addr_validate(&res.contract_address)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let funding_amount = config.pool_funding_amount();
+
+    // swaps out of the pool keep working under a restrictive policy
+    let update_exemptions_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.token_contract.to_string(),
+        msg: to_binary(&TokenExecuteMsg::UpdateExemptions {
+            add: vec![config.pair_contract.to_string()],
+            remove: vec![],
+        })?,
+        funds: vec![],
+    });
+
+    let mut increase_allowance_msgs = vec![increase_allowance_msg(
+        &config.token_contract,
+        &config.pair_contract,
+        pool.channel_token_amount,
+    )?];
+
+    let funds = match &config.funding_asset {
+        AssetInfo::Token { contract_addr } => {
+            increase_allowance_msgs.push(increase_allowance_msg(
+                contract_addr,
+                &config.pair_contract,
+                funding_amount,
+            )?);
+            vec![]
+        }
+        AssetInfo::NativeToken { denom } => vec![coin(funding_amount.u128(), denom)],
+    };
+
+    let provide_liquidity_msg = SubMsg {
+        id: PROVIDE_LIQUIDITY_REPLY_ID,
+        msg: WasmMsg::Execute {
+            contract_addr: config.pair_contract.to_string(),
+            msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+                assets: [
+                    Asset {
+                        info: AssetInfo::Token {
+                            contract_addr: config.token_contract.clone(),
+                        },
+                        amount: pool.channel_token_amount,
+                    },
+                    Asset {
+                        info: config.funding_asset.clone(),
+                        amount: funding_amount,
+                    },
+                ],
+                slippage_tolerance: None,
+            })?,
+            funds,
+        }
+        .into(),
+        gas_limit: None,
+        // LP tokens only have to be forwarded when a treasury is configured
+        reply_on: if pool.treasury.is_some() {
+            ReplyOn::Success
+        } else {
+            ReplyOn::Never
+        },
+    };
+
+    Ok(Response::new()
+        .add_attribute("pair_instantiate", config.pair_contract)
+        .add_attribute("pool_channel_token_amount", pool.channel_token_amount)
+        .add_attribute("pool_funding_amount", funding_amount)
+        .add_message(update_exemptions_msg)
+        .add_messages(increase_allowance_msgs)
+        .add_submessage(provide_liquidity_msg))
+}
+
+fn increase_allowance_msg(
+    token_addr: &Addr,
+    spender: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token_addr.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+            spender: spender.to_string(),
+            amount,
+            expires: None,
+        })?,
+        funds: vec![],
+    }))
+}
+
+fn reply_provide_liquidity(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let treasury = config
+        .pool
+        .and_then(|pool| pool.treasury)
+        .ok_or(ContractError::NotFound {})?;
+
+    let pair_info: PairInfoResponse = deps
+        .querier
+        .query_wasm_smart(&config.pair_contract, &PairQueryMsg::PairInfo {})?;
+
+    let lp_token_amount = query_token_balance(
+        &deps.querier,
+        &pair_info.liquidity_token,
+        &env.contract.address,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("lp_token_amount", lp_token_amount)
+        .add_attribute("treasury", treasury.clone())
+        .add_message(get_cw20_transfer_msg(
+            &treasury,
+            &pair_info.liquidity_token,
+            lp_token_amount,
+        )?))
 }
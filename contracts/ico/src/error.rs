@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -26,4 +26,46 @@ pub enum ContractError {
 
     #[error("Token already distributed")]
     TokenAlreadyDistributed {},
+
+    #[error("Invalid pool config")]
+    InvalidPoolConfig {},
+
+    #[error("Invalid vesting schedule: cliff must not exceed duration")]
+    InvalidVestingSchedule {},
+
+    #[error("Vesting is not configured")]
+    VestingNotConfigured {},
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("Not whitelisted")]
+    NotWhitelisted {},
+
+    #[error("Contribution is below the minimum of {min}")]
+    ContributionTooSmall { min: Uint128 },
+
+    #[error("Contribution cap per address reached")]
+    ContributionCapReached {},
+
+    #[error("No channel token holders to distribute revenue to")]
+    NoTokenHolders {},
+
+    #[error("Invalid funding asset")]
+    InvalidFundingAsset {},
+
+    #[error("Funding not started")]
+    FundingNotStarted {},
+
+    #[error("Invalid deadline: it must be in the future and after the start")]
+    InvalidDeadline {},
+
+    #[error("Invalid funding cap: soft cap must be positive and not exceed hard cap")]
+    InvalidFundingCap {},
+
+    #[error("Invalid contribution limits: min must not exceed max")]
+    InvalidContributionLimits {},
+
+    #[error("Transfer amount exceeds the transferable funds of {available}")]
+    TransferAmountExceeded { available: Uint128 },
 }
@@ -1,23 +1,76 @@
 use crate::contract::END_FUNDING_REPLAY_ID;
 use crate::error::ContractError;
-use crate::state::{CONFIG, FUNDING};
+use crate::msg::Cw20HookMsg;
+use crate::revenue;
+use crate::state::{
+    Config, CLAIMED, CONFIG, DISTRIBUTION_CURSOR, FUNDING, TRANSFERRED_FUND, WHITELIST,
+};
 use cosmwasm_std::{
-    to_binary, Addr, CosmosMsg, DepsMut, Env, MessageInfo, ReplyOn, Response, StdError, SubMsg,
-    Uint128, WasmMsg,
+    from_binary, to_binary, Addr, BlockInfo, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo,
+    Order, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
-use cw20::{AllAccountsResponse, MinterResponse, TokenInfoResponse};
-use cw20_base::msg::QueryMsg::{AllAccounts, TokenInfo};
-use ysip::querier::query_token_balance;
-use ysip::utils::get_bank_transfer_to_msg;
+use cw20::{Cw20ReceiveMsg, MinterResponse};
+use cw_storage_plus::Bound;
+use token::msg::{ExecuteMsg as TokenExecuteMsg, InstantiateMsg as TokenInstantiateMsg};
+use ysip::asset::{Asset, AssetInfo};
+use ysip::utils::{get_asset_transfer_msg, get_cw20_transfer_msg};
 
-pub fn fund_channel_token(
+pub fn receive_cw20(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let sent_asset_info = AssetInfo::Token {
+        contract_addr: info.sender,
+    };
+    if sent_asset_info != config.funding_asset {
+        return Err(ContractError::InvalidFundingAsset {});
+    }
+
+    if cw20_msg.amount.is_zero() {
+        return Err(ContractError::InvalidCoinAmount {});
+    }
+
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::FundChannelToken {} => {
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            fund_channel_token(deps, env, sender, cw20_msg.amount)
+        }
+        Cw20HookMsg::Allocation {} => allocation(deps, env, cw20_msg.amount),
+    }
+}
+
+/// amount of the native funding asset sent with the message
+pub fn received_native_amount(deps: Deps, info: &MessageInfo) -> Result<Uint128, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    match config.funding_asset {
+        AssetInfo::NativeToken { denom } => info
+            .funds
+            .iter()
+            .find(|fund| fund.denom == denom)
+            .map(|fund| fund.amount)
+            .ok_or(ContractError::InvalidCoinAmount {}),
+        // cw20 funding asset has to be sent through the Receive hook
+        AssetInfo::Token { .. } => Err(ContractError::InvalidFundingAsset {}),
+    }
+}
+
+pub fn fund_channel_token(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    input_amount: Uint128,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
 
-    if env.block.height > config.deadline {
+    if !config.is_started(&env.block) {
+        return Err(ContractError::FundingNotStarted {});
+    }
+
+    if config.is_expired(&env.block) {
         return Err(ContractError::FundingFinished {});
     }
 
@@ -25,41 +78,61 @@ pub fn fund_channel_token(
         return Err(ContractError::FundingFinished {});
     }
 
-    let max_fund_available_amount = config
-        .target_funding_amount
+    if config.whitelist_enabled && !WHITELIST.has(deps.storage, sender.clone()) {
+        return Err(ContractError::NotWhitelisted {});
+    }
+
+    let mut max_fund_available_amount = config
+        .hard_cap
         .checked_sub(config.current_funding_amount)
         .map_err(StdError::overflow)?;
 
-    let input_coin = info
-        .funds
-        .iter()
-        .find(|fund| fund.denom == "ukrw")
-        .ok_or_else(|| ContractError::InvalidCoinAmount {})?;
+    if let Some(max_contribution) = config.max_contribution_per_address {
+        let funded_amount = FUNDING
+            .may_load(deps.storage, sender.clone())?
+            .unwrap_or_default();
+        let max_contribution_available = max_contribution.saturating_sub(funded_amount);
+
+        if max_contribution_available.is_zero() {
+            return Err(ContractError::ContributionCapReached {});
+        }
+
+        max_fund_available_amount = max_fund_available_amount.min(max_contribution_available);
+    }
 
-    if input_coin.amount == Uint128::zero() {
+    if input_amount == Uint128::zero() {
         return Err(ContractError::InvalidCoinAmount {});
     }
 
+    if let Some(min_contribution) = config.min_contribution {
+        if input_amount < min_contribution {
+            return Err(ContractError::ContributionTooSmall {
+                min: min_contribution,
+            });
+        }
+    }
+
     let (fund_amount, refund_amount) = {
-        if max_fund_available_amount < input_coin.amount {
-            let refund_amount = input_coin
-                .amount
+        if max_fund_available_amount < input_amount {
+            let refund_amount = input_amount
                 .checked_sub(max_fund_available_amount)
                 .map_err(StdError::overflow)?;
             (max_fund_available_amount, refund_amount)
         } else {
-            (input_coin.amount, Uint128::zero())
+            (input_amount, Uint128::zero())
         }
     };
 
     let mut refund_msg: Vec<CosmosMsg> = vec![];
 
     if !refund_amount.eq(&Uint128::zero()) {
-        refund_msg.push(get_bank_transfer_to_msg(
-            &info.sender,
-            "ukrw",
-            refund_amount,
-        ));
+        refund_msg.push(get_asset_transfer_msg(
+            &sender,
+            Asset {
+                info: config.funding_asset.clone(),
+                amount: refund_amount,
+            },
+        )?);
     }
 
     config.current_funding_amount = config
@@ -67,7 +140,7 @@ pub fn fund_channel_token(
         .checked_add(fund_amount)
         .map_err(StdError::overflow)?;
 
-    if config.current_funding_amount >= config.target_funding_amount {
+    if config.current_funding_amount >= config.hard_cap {
         config.finished = true;
     }
 
@@ -75,7 +148,7 @@ pub fn fund_channel_token(
 
     FUNDING.update(
         deps.storage,
-        info.sender,
+        sender,
         |funding| -> Result<_, ContractError> {
             let new_funding = match funding {
                 Some(funding) => funding
@@ -99,7 +172,7 @@ pub fn end_funding(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respons
         return Err(ContractError::TokenAlreadyDistributed {});
     }
 
-    if !config.is_finished() && env.block.height < config.deadline {
+    if !config.is_finished() && !config.is_expired(&env.block) {
         return Err(ContractError::FundingNotFinished {});
     }
 
@@ -109,13 +182,14 @@ pub fn end_funding(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respons
 
     let mut sub_msg: Vec<SubMsg> = vec![];
 
-    if config.is_finished() {
+    // nothing is minted if the soft cap is not reached, funders can request refund instead
+    if config.is_successful(&env.block) {
         sub_msg.push(SubMsg {
             id: END_FUNDING_REPLAY_ID,
             msg: WasmMsg::Instantiate {
                 admin: Some(info.sender.to_string()),
                 code_id: config.token_code_id,
-                msg: to_binary(&cw20_base::msg::InstantiateMsg {
+                msg: to_binary(&TokenInstantiateMsg {
                     name: config.token_name.to_string(),
                     symbol: config.token_symbol.to_string(),
                     decimals: 6,
@@ -125,6 +199,8 @@ pub fn end_funding(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respons
                         cap: None,
                     }),
                     marketing: None,
+                    // hooks, transfer policy and locks are managed through the ico
+                    admin: Some(env.contract.address.to_string()),
                 })?,
                 funds: vec![],
                 label: format!("{} channel token", config.token_name),
@@ -147,11 +223,11 @@ pub fn end_funding(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respons
 pub fn refund(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
-    if env.block.height < config.deadline {
+    if !config.is_expired(&env.block) {
         return Err(ContractError::FundingNotFinished {});
     }
 
-    if config.is_finished() {
+    if config.is_successful(&env.block) {
         return Err(ContractError::FundingFinished {});
     }
 
@@ -161,7 +237,13 @@ pub fn refund(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, Co
 
     match funded_amount {
         Some(amount) => {
-            msgs.push(get_bank_transfer_to_msg(&info.sender, "ukrw", amount));
+            msgs.push(get_asset_transfer_msg(
+                &info.sender,
+                Asset {
+                    info: config.funding_asset.clone(),
+                    amount,
+                },
+            )?);
         }
         None => return Err(ContractError::NotFound {}),
     }
@@ -176,7 +258,7 @@ pub fn refund(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, Co
 
 pub fn transfer_fund(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
@@ -185,11 +267,28 @@ pub fn transfer_fund(
         return Err(ContractError::Unauthorized {});
     }
 
-    if !config.is_finished() {
+    if !config.is_successful(&env.block) {
         return Err(ContractError::FundingNotFinished {});
     }
 
-    let transfer_msg = get_bank_transfer_to_msg(&config.recipient, "ukrw", amount);
+    // the funding asset balance also holds the pool seed and the unclaimed revenue
+    let transferred = TRANSFERRED_FUND.may_load(deps.storage)?.unwrap_or_default();
+    let available = config
+        .current_funding_amount
+        .saturating_sub(config.pool_funding_amount())
+        .saturating_sub(transferred);
+    if amount > available {
+        return Err(ContractError::TransferAmountExceeded { available });
+    }
+    TRANSFERRED_FUND.save(deps.storage, &(transferred + amount))?;
+
+    let transfer_msg = get_asset_transfer_msg(
+        &config.recipient,
+        Asset {
+            info: config.funding_asset.clone(),
+            amount,
+        },
+    )?;
 
     Ok(Response::new()
         .add_attribute("action", "transfer_fund")
@@ -198,101 +297,297 @@ pub fn transfer_fund(
         .add_message(transfer_msg))
 }
 
-pub fn allocation(
+pub fn allocation(deps: DepsMut, env: Env, amount: Uint128) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if config.token_contract.as_str().is_empty() {
+        return Err(ContractError::FundingNotFinished {});
+    }
+
+    let record = revenue::deposit_revenue(deps.storage, &env.block, amount)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "allocation")
+        .add_attribute("revenue_id", record.id.to_string())
+        .add_attribute("amount", amount))
+}
+
+pub fn claim_revenue(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let amount = revenue::claim_revenue(deps.storage, &info.sender)?;
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let transfer_msg = get_asset_transfer_msg(
+        &info.sender,
+        Asset {
+            info: config.funding_asset,
+            amount,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "claim_revenue")
+        .add_attribute("amount", amount)
+        .add_message(transfer_msg))
+}
+
+pub fn balance_changed(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    amount: Uint128,
+    address: String,
+    new_balance: Uint128,
 ) -> Result<Response, ContractError> {
-    let fund = info
-        .funds
-        .iter()
-        .find(|coin| coin.denom == "ukrw")
-        .expect("coin not found");
-    if fund.amount != amount {
-        return Err(ContractError::InvalidCoinAmount {});
+    let config = CONFIG.load(deps.storage)?;
+    if config.token_contract != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    // unclaimed channel tokens held by the ico contract and the pool of the pair don't earn revenue
+    revenue::update_holder_balance(
+        deps.storage,
+        &[&env.contract.address, &config.pair_contract],
+        &address,
+        new_balance,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "balance_changed")
+        .add_attribute("address", address)
+        .add_attribute("new_balance", new_balance))
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub fn claim_vested(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    match (&config.vesting, &config.vesting_start) {
+        (Some(_), Some(_)) => {}
+        (Some(_), None) => return Err(ContractError::FundingNotFinished {}),
+        _ => return Err(ContractError::VestingNotConfigured {}),
+    };
+
+    let (transfer_msg, amount) = claim(deps.storage, &config, &info.sender, &env.block)?
+        .ok_or(ContractError::NothingToClaim {})?;
+
+    Ok(Response::new()
+        .add_attribute("action", "claim_vested")
+        .add_attribute("amount", amount)
+        .add_message(transfer_msg))
+}
+
+pub fn claim_tokens(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if config.token_contract.as_str().is_empty() {
+        return Err(ContractError::FundingNotFinished {});
     }
 
+    let (transfer_msg, amount) = claim(deps.storage, &config, &info.sender, &env.block)?
+        .ok_or(ContractError::NothingToClaim {})?;
+
+    Ok(Response::new()
+        .add_attribute("action", "claim_tokens")
+        .add_attribute("amount", amount)
+        .add_message(transfer_msg))
+}
+
+pub fn distribute_batch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
+    if config.admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
 
-    let all_accounts: AllAccountsResponse = deps.querier.query_wasm_smart(
-        config.token_contract.clone(),
-        &AllAccounts {
-            start_after: None,
-            limit: None,
-        },
-    )?;
+    if config.token_contract.as_str().is_empty() {
+        return Err(ContractError::FundingNotFinished {});
+    }
 
-    let token_info: TokenInfoResponse = deps
-        .querier
-        .query_wasm_smart(config.token_contract.clone(), &TokenInfo {})?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let cursor = DISTRIBUTION_CURSOR.may_load(deps.storage)?;
+    let start = cursor.map(Bound::exclusive);
 
-    let total_supply = token_info.total_supply;
+    let funders = FUNDING
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<Addr>>>()?;
 
     let mut transfer_msgs: Vec<CosmosMsg> = vec![];
-
-    for account in all_accounts.accounts {
-        let balance = query_token_balance(
-            &deps.querier,
-            &config.token_contract,
-            &Addr::unchecked(account.clone()),
-        )
-        .expect("token balance not found");
-
-        if !balance.eq(&Uint128::zero()) {
-            transfer_msgs.push(get_bank_transfer_to_msg(
-                &Addr::unchecked(account),
-                "ukrw",
-                fund.amount
-                    .checked_multiply_ratio(balance, total_supply)
-                    .expect("overflow"),
-            ));
+    for funder in &funders {
+        if let Some((msg, _)) = claim(deps.storage, &config, funder, &env.block)? {
+            transfer_msgs.push(msg);
         }
     }
 
+    // start over from the first funder once everyone is visited, so vested tokens
+    // can be distributed again later
+    match funders.last() {
+        Some(last) if funders.len() == limit => DISTRIBUTION_CURSOR.save(deps.storage, last)?,
+        _ => DISTRIBUTION_CURSOR.remove(deps.storage),
+    }
+
     Ok(Response::new()
-        .add_attribute("action", "allocation")
+        .add_attribute("action", "distribute_batch")
+        .add_attribute("paid_count", transfer_msgs.len().to_string())
         .add_messages(transfer_msgs))
 }
 
+/// pays everything unlocked but not yet claimed to `funder`, returns None if nothing is claimable
+fn claim(
+    storage: &mut dyn Storage,
+    config: &Config,
+    funder: &Addr,
+    block: &BlockInfo,
+) -> Result<Option<(CosmosMsg, Uint128)>, ContractError> {
+    let funded_amount = FUNDING
+        .may_load(storage, funder.clone())?
+        .ok_or(ContractError::NotFound {})?;
+    let unlocked = config.unlocked_amount(funded_amount, block)?;
+    let claimed = CLAIMED
+        .may_load(storage, funder.clone())?
+        .unwrap_or_default();
+
+    let claimable = unlocked.checked_sub(claimed).map_err(StdError::overflow)?;
+    if claimable.is_zero() {
+        return Ok(None);
+    }
+
+    CLAIMED.save(storage, funder.clone(), &unlocked)?;
+
+    let transfer_msg = get_cw20_transfer_msg(funder, &config.token_contract, claimable)?;
+
+    Ok(Some((transfer_msg, claimable)))
+}
+
+pub fn update_whitelist(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for addr in &add {
+        WHITELIST.save(deps.storage, deps.api.addr_validate(addr)?, &Empty {})?;
+    }
+
+    for addr in &remove {
+        WHITELIST.remove(deps.storage, deps.api.addr_validate(addr)?);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_whitelist")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string()))
+}
+
+/// forwards an admin message to the channel token, whose admin is the ico contract
+pub fn update_channel_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: TokenExecuteMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if config.token_contract.as_str().is_empty() {
+        return Err(ContractError::FundingNotFinished {});
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_channel_token")
+        .add_message(WasmMsg::Execute {
+            contract_addr: config.token_contract.to_string(),
+            msg: to_binary(&msg)?,
+            funds: vec![],
+        }))
+}
+
 #[cfg(test)]
 mod test_ico {
-    use crate::execute::{end_funding, fund_channel_token};
-    use crate::state::{Config, CONFIG, FUNDING};
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coin, Addr, Uint128};
+    use crate::error::ContractError;
+    use crate::execute::{
+        allocation, claim_tokens, distribute_batch, end_funding, fund_channel_token, receive_cw20,
+        refund, transfer_fund, update_whitelist,
+    };
+    use crate::msg::Cw20HookMsg;
+    use crate::revenue::update_holder_balance;
+    use crate::state::{Config, PoolConfig, CONFIG, FUNDING};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockStorage};
+    use cosmwasm_std::{coin, to_binary, Addr, Decimal, Uint128};
+    use cw20::Cw20ReceiveMsg;
+    use cw_utils::{Expiration, Scheduled};
+    use ysip::asset::AssetInfo;
 
     const ADDR: &str = "cosmos18zfp9u7zxg3gel4r3txa2jqxme7jkw7dnvfjc8";
+    const FUNDER: &str = "funder";
+
+    fn mock_config() -> Config {
+        Config {
+            admin: Addr::unchecked(ADDR),
+            token_code_id: 1,
+            token_name: "channel".to_string(),
+            token_symbol: "CHANNEL".to_string(),
+            soft_cap: Uint128::new(50),
+            hard_cap: Uint128::new(100),
+            funding_asset: AssetInfo::NativeToken {
+                denom: "ukrw".to_string(),
+            },
+            current_funding_amount: Uint128::zero(),
+            channel_token_amount: Uint128::new(100000),
+            start: None,
+            deadline: Expiration::AtHeight(12_346),
+            finished: false,
+            token_contract: Addr::unchecked(""),
+            pair_contract: Addr::unchecked(""),
+            recipient: Addr::unchecked(ADDR),
+            is_token_distributed: false,
+            pool: None,
+            vesting: None,
+            vesting_start: None,
+            whitelist_enabled: false,
+            min_contribution: None,
+            max_contribution_per_address: None,
+        }
+    }
+
+    fn save_config(storage: &mut MockStorage, config: &Config) {
+        CONFIG.save(storage, config).unwrap();
+    }
 
     #[test]
     fn test_fund_channel_token() {
         let mut deps = mock_dependencies();
-        let config = CONFIG
-            .save(
-                &mut deps.storage,
-                &Config {
-                    admin: Addr::unchecked(ADDR),
-                    token_code_id: 1,
-                    pair_code_id: 2,
-                    token_name: "channel".to_string(),
-                    token_symbol: "CHANNEL".to_string(),
-                    target_funding_amount: Uint128::new(100),
-                    current_funding_amount: Uint128::zero(),
-                    channel_token_amount: Uint128::new(100000),
-                    deadline: 12_346,
-                    finished: false,
-                    token_contract: Addr::unchecked(""),
-                    recipient: Addr::unchecked(ADDR),
-                },
-            )
-            .unwrap();
+        save_config(&mut deps.storage, &mock_config());
 
         let res = fund_channel_token(
             deps.as_mut(),
             mock_env(),
-            mock_info(ADDR, &[coin(10000, "ukrw")]),
+            Addr::unchecked(ADDR),
+            Uint128::new(10000),
         )
         .unwrap();
+        // everything above the target is refunded
+        assert_eq!(res.messages.len(), 1);
+
         let funding = FUNDING.load(&deps.storage, Addr::unchecked(ADDR)).unwrap();
+        assert_eq!(funding, Uint128::new(100));
 
         let res = end_funding(
             deps.as_mut(),
@@ -300,5 +595,361 @@ mod test_ico {
             mock_info(ADDR, &[coin(10000, "ukrw")]),
         )
         .unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_whitelist_and_contribution_limits() {
+        let mut deps = mock_dependencies();
+        save_config(
+            &mut deps.storage,
+            &Config {
+                whitelist_enabled: true,
+                min_contribution: Some(Uint128::new(10)),
+                max_contribution_per_address: Some(Uint128::new(30)),
+                ..mock_config()
+            },
+        );
+
+        let err = fund_channel_token(
+            deps.as_mut(),
+            mock_env(),
+            Addr::unchecked(FUNDER),
+            Uint128::new(20),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotWhitelisted {});
+
+        // only admin can update the whitelist
+        let err = update_whitelist(
+            deps.as_mut(),
+            mock_info(FUNDER, &[]),
+            vec![FUNDER.to_string()],
+            vec![],
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        update_whitelist(
+            deps.as_mut(),
+            mock_info(ADDR, &[]),
+            vec![FUNDER.to_string()],
+            vec![],
+        )
+        .unwrap();
+
+        let err = fund_channel_token(
+            deps.as_mut(),
+            mock_env(),
+            Addr::unchecked(FUNDER),
+            Uint128::new(5),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ContributionTooSmall {
+                min: Uint128::new(10)
+            }
+        );
+
+        fund_channel_token(
+            deps.as_mut(),
+            mock_env(),
+            Addr::unchecked(FUNDER),
+            Uint128::new(20),
+        )
+        .unwrap();
+
+        // everything above the per address cap is refunded
+        let res = fund_channel_token(
+            deps.as_mut(),
+            mock_env(),
+            Addr::unchecked(FUNDER),
+            Uint128::new(25),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        let funding = FUNDING
+            .load(&deps.storage, Addr::unchecked(FUNDER))
+            .unwrap();
+        assert_eq!(funding, Uint128::new(30));
+
+        let err = fund_channel_token(
+            deps.as_mut(),
+            mock_env(),
+            Addr::unchecked(FUNDER),
+            Uint128::new(10),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ContributionCapReached {});
+    }
+
+    #[test]
+    fn test_soft_cap() {
+        let mut deps = mock_dependencies();
+        save_config(&mut deps.storage, &mock_config());
+
+        fund_channel_token(
+            deps.as_mut(),
+            mock_env(),
+            Addr::unchecked(FUNDER),
+            Uint128::new(60),
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 12_346;
+
+        // above the soft cap after the deadline, funding is successful
+        let err = refund(deps.as_mut(), env.clone(), mock_info(FUNDER, &[])).unwrap_err();
+        assert_eq!(err, ContractError::FundingFinished {});
+
+        let res = end_funding(deps.as_mut(), env, mock_info(ADDR, &[])).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_refund_below_soft_cap() {
+        let mut deps = mock_dependencies();
+        save_config(&mut deps.storage, &mock_config());
+
+        fund_channel_token(
+            deps.as_mut(),
+            mock_env(),
+            Addr::unchecked(FUNDER),
+            Uint128::new(40),
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 12_346;
+
+        let res = end_funding(deps.as_mut(), env.clone(), mock_info(ADDR, &[])).unwrap();
+        assert!(res.messages.is_empty());
+
+        let res = refund(deps.as_mut(), env, mock_info(FUNDER, &[])).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert!(FUNDING
+            .may_load(&deps.storage, Addr::unchecked(FUNDER))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_funding_period() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        save_config(
+            &mut deps.storage,
+            &Config {
+                start: Some(Scheduled::AtTime(env.block.time.plus_seconds(100))),
+                deadline: Expiration::AtTime(env.block.time.plus_seconds(200)),
+                ..mock_config()
+            },
+        );
+
+        let err = fund_channel_token(
+            deps.as_mut(),
+            env.clone(),
+            Addr::unchecked(FUNDER),
+            Uint128::new(40),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::FundingNotStarted {});
+
+        let mut started = env.clone();
+        started.block.time = env.block.time.plus_seconds(100);
+        fund_channel_token(
+            deps.as_mut(),
+            started,
+            Addr::unchecked(FUNDER),
+            Uint128::new(40),
+        )
+        .unwrap();
+
+        let mut expired = env.clone();
+        expired.block.time = env.block.time.plus_seconds(200);
+        let err = fund_channel_token(
+            deps.as_mut(),
+            expired.clone(),
+            Addr::unchecked(FUNDER),
+            Uint128::new(40),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::FundingFinished {});
+
+        refund(deps.as_mut(), expired, mock_info(FUNDER, &[])).unwrap();
+    }
+
+    #[test]
+    fn test_fund_with_cw20() {
+        let mut deps = mock_dependencies();
+        save_config(
+            &mut deps.storage,
+            &Config {
+                funding_asset: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("stablecoin"),
+                },
+                ..mock_config()
+            },
+        );
+
+        let receive_msg = Cw20ReceiveMsg {
+            sender: FUNDER.to_string(),
+            amount: Uint128::new(150),
+            msg: to_binary(&Cw20HookMsg::FundChannelToken {}).unwrap(),
+        };
+
+        let err = receive_cw20(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[]),
+            receive_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidFundingAsset {});
+
+        let res = receive_cw20(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stablecoin", &[]),
+            receive_msg,
+        )
+        .unwrap();
+        // excess above the hard cap is refunded in the funding token
+        assert_eq!(res.messages.len(), 1);
+
+        let funding = FUNDING
+            .load(&deps.storage, Addr::unchecked(FUNDER))
+            .unwrap();
+        assert_eq!(funding, Uint128::new(100));
+    }
+
+    #[test]
+    fn test_distribute_batch() {
+        let mut deps = mock_dependencies();
+        save_config(
+            &mut deps.storage,
+            &Config {
+                current_funding_amount: Uint128::new(100),
+                finished: true,
+                token_contract: Addr::unchecked("channel"),
+                ..mock_config()
+            },
+        );
+        for funder in ["funder1", "funder2", "funder3"] {
+            FUNDING
+                .save(
+                    &mut deps.storage,
+                    Addr::unchecked(funder),
+                    &Uint128::new(25),
+                )
+                .unwrap();
+        }
+
+        let err =
+            distribute_batch(deps.as_mut(), mock_env(), mock_info(FUNDER, &[]), None).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res =
+            distribute_batch(deps.as_mut(), mock_env(), mock_info(ADDR, &[]), Some(2)).unwrap();
+        assert_eq!(res.messages.len(), 2);
+
+        let res = claim_tokens(deps.as_mut(), mock_env(), mock_info("funder3", &[])).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        // nobody is paid twice
+        let res =
+            distribute_batch(deps.as_mut(), mock_env(), mock_info(ADDR, &[]), Some(2)).unwrap();
+        assert!(res.messages.is_empty());
+
+        let err = claim_tokens(deps.as_mut(), mock_env(), mock_info("funder1", &[])).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+    }
+
+    #[test]
+    fn test_transfer_fund() {
+        let mut deps = mock_dependencies();
+        save_config(
+            &mut deps.storage,
+            &Config {
+                current_funding_amount: Uint128::new(100),
+                finished: true,
+                token_contract: Addr::unchecked("channel"),
+                pool: Some(PoolConfig {
+                    pair_code_id: 2,
+                    funding_ratio: Decimal::percent(40),
+                    channel_token_amount: Uint128::new(1000),
+                    protocol_fee_recipient: Addr::unchecked(ADDR),
+                    protocol_fee_percent: "0.15".to_string(),
+                    lp_fee_percent: "0.15".to_string(),
+                    treasury: None,
+                }),
+                ..mock_config()
+            },
+        );
+
+        // revenue for holders sits in the same balance as the raised funds
+        update_holder_balance(
+            &mut deps.storage,
+            &[],
+            &Addr::unchecked(FUNDER),
+            Uint128::new(1000),
+        )
+        .unwrap();
+        allocation(deps.as_mut(), mock_env(), Uint128::new(500)).unwrap();
+
+        let err = transfer_fund(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(FUNDER, &[]),
+            Uint128::new(10),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // 40 of the 100 raised seed the pool
+        transfer_fund(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADDR, &[]),
+            Uint128::new(50),
+        )
+        .unwrap();
+        let err = transfer_fund(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADDR, &[]),
+            Uint128::new(500),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TransferAmountExceeded {
+                available: Uint128::new(10)
+            }
+        );
+
+        transfer_fund(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADDR, &[]),
+            Uint128::new(10),
+        )
+        .unwrap();
+        let err = transfer_fund(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADDR, &[]),
+            Uint128::new(1),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TransferAmountExceeded {
+                available: Uint128::zero()
+            }
+        );
     }
 }
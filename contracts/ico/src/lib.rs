@@ -3,5 +3,6 @@ pub mod error;
 pub mod execute;
 pub mod msg;
 pub mod query;
+pub mod revenue;
 pub mod state;
 pub mod utils;
@@ -1,45 +1,146 @@
-use cosmwasm_std::Uint128;
+use crate::state::{RevenueRecord, VestingSchedule};
+use cosmwasm_std::{Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw_utils::{Expiration, Scheduled};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use ysip::asset::AssetInfo;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-    pub target_funding: Uint128,
-    /// block height for deadline
-    pub deadline: u64,
+    /// minimum funding amount for the ico to succeed once the deadline has passed
+    pub soft_cap: Uint128,
+    /// funding stops as soon as it reaches the hard cap
+    pub hard_cap: Uint128,
+    /// native denom or cw20 token raised by the ico
+    pub funding_asset: AssetInfo,
+    /// funding is accepted from the start (immediately if not set) until the deadline
+    pub start: Option<Scheduled>,
+    /// block height or time for deadline
+    pub deadline: Expiration,
+    /// code id of the channel token contract, which has to support hooks
     pub token_code_id: u64,
     pub token_name: String,
     pub token_symbol: String,
     pub channel_token_amount: Uint128,
     /// recipient who is getting fund
     pub recipient: String,
+    /// if set, a channel token / funding asset pair is seeded when funding ends successfully
+    pub pool: Option<PoolConfigMsg>,
+    /// if set, funders claim their channel tokens with ClaimVested as they vest
+    pub vesting: Option<VestingSchedule>,
+    /// if set, only whitelisted addresses can fund
+    pub whitelist: Option<Vec<String>>,
+    pub min_contribution: Option<Uint128>,
+    pub max_contribution_per_address: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PoolConfigMsg {
+    pub pair_code_id: u64,
+    /// ratio of the raised funding asset provided as initial liquidity
+    pub funding_ratio: Decimal,
+    /// channel token amount minted on top of `channel_token_amount` for the initial liquidity
+    pub channel_token_amount: Uint128,
+    pub protocol_fee_recipient: String,
+    pub protocol_fee_percent: String,
+    pub lp_fee_percent: String,
+    /// LP tokens are sent to the treasury, or stay locked in the ico contract if not set
+    pub treasury: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    /// funding with a cw20 funding asset goes through the Receive hook
+    Receive(Cw20ReceiveMsg),
     FundChannelToken {},
     /// only admin of ico contract can call EndFunding
     EndFunding {},
     /// if deadline ends, user can request refund
     Refund {},
-    /// transfer fund to the recipient
+    /// transfer fund to the recipient, at most the raised funds not provided to the pool and not
+    /// transferred yet, revenue deposited for holders is never transferred
     TransferFund {
         amount: Uint128,
     },
+    /// deposit revenue for channel token holders
     Allocation {
         amount: Uint128,
     },
+    /// claim revenue accrued to the channel tokens held by the sender
+    ClaimRevenue {},
+    /// called by the channel token whenever a balance changes
+    BalanceChanged {
+        address: String,
+        old_balance: Uint128,
+        new_balance: Uint128,
+    },
+    /// claim channel tokens vested so far
+    ClaimVested {},
+    /// claim purchased channel tokens after funding ended successfully
+    ClaimTokens {},
+    /// only admin of ico contract can pay unclaimed channel tokens to the next `limit` funders
+    DistributeBatch {
+        limit: Option<u32>,
+    },
+    /// only admin of ico contract can update the whitelist
+    UpdateWhitelist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// only admin of ico contract can disable channel token transfers until `transfers_enabled_at`
+    UpdateTransferPolicy {
+        transfers_enabled_at: Option<Scheduled>,
+    },
+    /// only admin of ico contract can lock channel tokens of an address until `unlock_at`
+    LockAddress {
+        address: String,
+        unlock_at: Scheduled,
+    },
+    /// only admin of ico contract can unlock channel tokens of an address
+    UnlockAddress {
+        address: String,
+    },
+    /// only admin of ico contract can update addresses exempted from the channel token
+    /// transfer policy and locks
+    UpdateExemptions {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    FundChannelToken {},
+    Allocation {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     IcoInfo {},
-    FundingAmount { addr: String },
+    FundingAmount {
+        addr: String,
+    },
     TotalFundingAmount {},
     IsFundingFinished {},
     TokenAddress {},
+    PairAddress {},
+    VestingInfo {
+        addr: String,
+    },
+    IsWhitelisted {
+        addr: String,
+    },
+    PendingRevenue {
+        addr: String,
+    },
+    RevenueHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -60,6 +161,13 @@ pub struct IsFundingFinishedResponse {
     pub status: bool,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct IsWhitelistedResponse {
+    /// always true if the whitelist is not enabled
+    pub status: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct TokenAddressResponse {
@@ -78,6 +186,29 @@ pub struct IcoInfoResponse {
     pub admin: String,
     pub token_name: String,
     pub token_symbol: String,
-    pub target_funding_amount: Uint128,
-    pub deadline: u64,
+    pub soft_cap: Uint128,
+    pub hard_cap: Uint128,
+    pub funding_asset: AssetInfo,
+    pub start: Option<Scheduled>,
+    pub deadline: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VestingInfoResponse {
+    pub total: Uint128,
+    pub claimed: Uint128,
+    pub claimable: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingRevenueResponse {
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RevenueHistoryResponse {
+    pub revenues: Vec<RevenueRecord>,
 }
@@ -1,49 +1,125 @@
 use crate::msg::{
-    FundingAmountResponse, IcoInfoResponse, IsFundingFinishedResponse, TokenAddressResponse,
-    TotalFundingAmountResponse,
+    FundingAmountResponse, IcoInfoResponse, IsFundingFinishedResponse, IsWhitelistedResponse,
+    PairAddressResponse, PendingRevenueResponse, RevenueHistoryResponse, TokenAddressResponse,
+    TotalFundingAmountResponse, VestingInfoResponse,
 };
-use crate::state::{CONFIG, FUNDING};
+use crate::revenue;
+use crate::state::{CLAIMED, CONFIG, FUNDING, HOLDERS, REVENUE_HISTORY, REVENUE_STATE, WHITELIST};
 use cosmwasm_std::{to_binary, Binary, Deps, Env, Order, StdResult, Uint128};
+use cw_storage_plus::Bound;
 
 pub fn ico_info(deps: Deps) -> StdResult<Binary> {
     let config = CONFIG.load(deps.storage)?;
-    Ok(to_binary(&IcoInfoResponse {
+    to_binary(&IcoInfoResponse {
         admin: config.admin.to_string(),
         token_name: config.token_name.to_string(),
         token_symbol: config.token_symbol.to_string(),
-        target_funding_amount: config.target_funding_amount,
+        soft_cap: config.soft_cap,
+        hard_cap: config.hard_cap,
+        funding_asset: config.funding_asset,
+        start: config.start,
         deadline: config.deadline,
-    })?)
+    })
 }
 
 pub fn funding_amount(deps: Deps, addr: &str) -> StdResult<Binary> {
     let address = deps.api.addr_validate(addr)?;
     let funding = FUNDING.load(deps.storage, address)?;
-    Ok(to_binary(&FundingAmountResponse { amount: funding })?)
+    to_binary(&FundingAmountResponse { amount: funding })
 }
 
 pub fn total_funding_amount(deps: Deps) -> StdResult<Binary> {
     let funding: Uint128 = FUNDING
         .range(deps.storage, None, None, Order::Descending)
-        .into_iter()
         .filter_map(|val| val.ok())
         .map(|val| val.1)
         .sum();
 
-    Ok(to_binary(&TotalFundingAmountResponse { amount: funding })?)
+    to_binary(&TotalFundingAmountResponse { amount: funding })
 }
 
 pub fn funding_finished(deps: Deps, env: Env) -> StdResult<Binary> {
     let config = CONFIG.load(deps.storage)?;
-    match config.is_finished() || env.block.height > config.deadline {
-        true => Ok(to_binary(&IsFundingFinishedResponse { status: true })?),
-        false => Ok(to_binary(&IsFundingFinishedResponse { status: false })?),
+    match config.is_finished() || config.is_expired(&env.block) {
+        true => to_binary(&IsFundingFinishedResponse { status: true }),
+        false => to_binary(&IsFundingFinishedResponse { status: false }),
     }
 }
 
 pub fn token_address(deps: Deps) -> StdResult<Binary> {
     let config = CONFIG.load(deps.storage)?;
-    Ok(to_binary(&TokenAddressResponse {
+    to_binary(&TokenAddressResponse {
         address: config.token_contract.to_string(),
-    })?)
+    })
+}
+
+pub fn pair_address(deps: Deps) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    to_binary(&PairAddressResponse {
+        address: config.pair_contract.to_string(),
+    })
+}
+
+pub fn vesting_info(deps: Deps, env: Env, addr: &str) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let address = deps.api.addr_validate(addr)?;
+
+    let funded_amount = FUNDING
+        .may_load(deps.storage, address.clone())?
+        .unwrap_or_default();
+    let (total, unlocked) = if config.current_funding_amount.is_zero() {
+        (Uint128::zero(), Uint128::zero())
+    } else {
+        (
+            config.channel_token_share(funded_amount)?,
+            config.unlocked_amount(funded_amount, &env.block)?,
+        )
+    };
+    let claimed = CLAIMED.may_load(deps.storage, address)?.unwrap_or_default();
+
+    to_binary(&VestingInfoResponse {
+        total,
+        claimed,
+        claimable: unlocked.saturating_sub(claimed),
+    })
+}
+
+pub fn is_whitelisted(deps: Deps, addr: &str) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let address = deps.api.addr_validate(addr)?;
+
+    to_binary(&IsWhitelistedResponse {
+        status: !config.whitelist_enabled || WHITELIST.has(deps.storage, address),
+    })
+}
+
+pub fn pending_revenue(deps: Deps, addr: &str) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(addr)?;
+    let state = REVENUE_STATE.may_load(deps.storage)?.unwrap_or_default();
+    let holder = HOLDERS.may_load(deps.storage, address)?.unwrap_or_default();
+
+    to_binary(&PendingRevenueResponse {
+        amount: revenue::pending_revenue(&state, &holder),
+    })
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub fn revenue_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let revenues = REVENUE_HISTORY
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, record)| record))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&RevenueHistoryResponse { revenues })
 }
@@ -0,0 +1,139 @@
+use crate::error::ContractError;
+use crate::state::{Holder, RevenueRecord, RevenueState, HOLDERS, REVENUE_HISTORY, REVENUE_STATE};
+use cosmwasm_std::{Addr, BlockInfo, Decimal, StdError, StdResult, Storage, Uint128};
+
+/// Returns the revenue accrued by `holder` until the current `reward_per_token`
+pub fn pending_revenue(state: &RevenueState, holder: &Holder) -> Uint128 {
+    holder.pending + holder.balance * (state.reward_per_token - holder.reward_per_token)
+}
+
+/// Raises the reward-per-token index with a revenue deposit shared by all tracked holders
+pub fn deposit_revenue(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    amount: Uint128,
+) -> Result<RevenueRecord, ContractError> {
+    let mut state = REVENUE_STATE.may_load(storage)?.unwrap_or_default();
+    if state.total_balance.is_zero() {
+        return Err(ContractError::NoTokenHolders {});
+    }
+
+    let reward_per_token = Decimal::checked_from_ratio(amount, state.total_balance)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    state.reward_per_token = state
+        .reward_per_token
+        .checked_add(reward_per_token)
+        .map_err(StdError::overflow)?;
+
+    let record = RevenueRecord {
+        id: state.revenue_count,
+        amount,
+        height: block.height,
+        time: block.time.seconds(),
+        reward_per_token: state.reward_per_token,
+    };
+
+    state.revenue_count += 1;
+    REVENUE_STATE.save(storage, &state)?;
+    REVENUE_HISTORY.save(storage, record.id, &record)?;
+
+    Ok(record)
+}
+
+/// Checkpoints the revenue accrued by `addr` with its previous balance, then tracks the new one.
+/// It must be called whenever the channel token balance of a holder changes.
+/// Channel tokens held by `protocol_addrs` don't earn revenue, they could never claim it
+pub fn update_holder_balance(
+    storage: &mut dyn Storage,
+    protocol_addrs: &[&Addr],
+    addr: &Addr,
+    new_balance: Uint128,
+) -> StdResult<()> {
+    if protocol_addrs.contains(&addr) {
+        return Ok(());
+    }
+
+    let mut state = REVENUE_STATE.may_load(storage)?.unwrap_or_default();
+    let mut holder = HOLDERS.may_load(storage, addr.clone())?.unwrap_or_default();
+
+    holder.pending = pending_revenue(&state, &holder);
+    holder.reward_per_token = state.reward_per_token;
+
+    state.total_balance = state
+        .total_balance
+        .checked_sub(holder.balance)?
+        .checked_add(new_balance)?;
+    holder.balance = new_balance;
+
+    REVENUE_STATE.save(storage, &state)?;
+    HOLDERS.save(storage, addr.clone(), &holder)
+}
+
+/// Resets the revenue accrued by `addr` and returns the amount to pay
+pub fn claim_revenue(storage: &mut dyn Storage, addr: &Addr) -> StdResult<Uint128> {
+    let state = REVENUE_STATE.may_load(storage)?.unwrap_or_default();
+    let mut holder = match HOLDERS.may_load(storage, addr.clone())? {
+        Some(holder) => holder,
+        None => return Ok(Uint128::zero()),
+    };
+
+    let pending = pending_revenue(&state, &holder);
+    holder.pending = Uint128::zero();
+    holder.reward_per_token = state.reward_per_token;
+    HOLDERS.save(storage, addr.clone(), &holder)?;
+
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod test_revenue {
+    use crate::revenue::{claim_revenue, deposit_revenue, update_holder_balance};
+    use cosmwasm_std::testing::{mock_env, MockStorage};
+    use cosmwasm_std::{Addr, Uint128};
+
+    #[test]
+    fn test_revenue_index() {
+        let mut storage = MockStorage::new();
+        let block = mock_env().block;
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        update_holder_balance(&mut storage, &[], &alice, Uint128::new(300)).unwrap();
+        update_holder_balance(&mut storage, &[], &bob, Uint128::new(100)).unwrap();
+        deposit_revenue(&mut storage, &block, Uint128::new(400)).unwrap();
+
+        // alice sends 200 tokens to bob, revenue deposited before is not moved with them
+        update_holder_balance(&mut storage, &[], &alice, Uint128::new(100)).unwrap();
+        update_holder_balance(&mut storage, &[], &bob, Uint128::new(300)).unwrap();
+        deposit_revenue(&mut storage, &block, Uint128::new(400)).unwrap();
+
+        assert_eq!(
+            claim_revenue(&mut storage, &alice).unwrap(),
+            Uint128::new(400)
+        );
+        assert_eq!(
+            claim_revenue(&mut storage, &bob).unwrap(),
+            Uint128::new(400)
+        );
+        assert_eq!(claim_revenue(&mut storage, &bob).unwrap(), Uint128::zero());
+    }
+
+    #[test]
+    fn test_protocol_addrs() {
+        let mut storage = MockStorage::new();
+        let block = mock_env().block;
+        let alice = Addr::unchecked("alice");
+        let pair = Addr::unchecked("pair");
+
+        update_holder_balance(&mut storage, &[&pair], &alice, Uint128::new(100)).unwrap();
+        update_holder_balance(&mut storage, &[&pair], &pair, Uint128::new(300)).unwrap();
+        deposit_revenue(&mut storage, &block, Uint128::new(400)).unwrap();
+
+        // the pair is not diluting alice
+        assert_eq!(
+            claim_revenue(&mut storage, &alice).unwrap(),
+            Uint128::new(400)
+        );
+        assert_eq!(claim_revenue(&mut storage, &pair).unwrap(), Uint128::zero());
+    }
+}
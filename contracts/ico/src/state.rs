@@ -1,7 +1,9 @@
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, BlockInfo, Decimal, Empty, StdError, StdResult, Uint128};
 use cw_storage_plus::{Item, Map};
+use cw_utils::{Expiration, Scheduled};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use ysip::asset::AssetInfo;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -10,11 +12,17 @@ pub struct Config {
     pub token_code_id: u64,
     pub token_name: String,
     pub token_symbol: String,
-    pub target_funding_amount: Uint128,
+    /// minimum funding amount for the ico to succeed once the deadline has passed
+    pub soft_cap: Uint128,
+    /// funding is finished as soon as it reaches the hard cap
+    pub hard_cap: Uint128,
+    /// native denom or cw20 token raised by the ico
+    pub funding_asset: AssetInfo,
     pub current_funding_amount: Uint128,
     /// Circulating channel token amount
     pub channel_token_amount: Uint128,
-    pub deadline: u64,
+    pub start: Option<Scheduled>,
+    pub deadline: Expiration,
     pub finished: bool,
     pub token_contract: Addr,
     pub pair_contract: Addr,
@@ -22,13 +30,214 @@ pub struct Config {
     pub recipient: Addr,
     /// is token already distributed after fund
     pub is_token_distributed: bool,
+    /// initial liquidity of the pair seeded when funding ends
+    pub pool: Option<PoolConfig>,
+    /// if set, purchased channel tokens stay in the ico contract until they are vested
+    pub vesting: Option<VestingSchedule>,
+    /// set when the channel token is minted
+    pub vesting_start: Option<VestingStart>,
+    /// only addresses in `WHITELIST` can fund if enabled
+    pub whitelist_enabled: bool,
+    pub min_contribution: Option<Uint128>,
+    pub max_contribution_per_address: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PoolConfig {
+    pub pair_code_id: u64,
+    /// ratio of the raised funding asset provided as initial liquidity
+    pub funding_ratio: Decimal,
+    /// channel token amount minted on top of `channel_token_amount` for the initial liquidity
+    pub channel_token_amount: Uint128,
+    pub protocol_fee_recipient: Addr,
+    pub protocol_fee_percent: String,
+    pub lp_fee_percent: String,
+    /// LP tokens are locked in the ico contract if not set
+    pub treasury: Option<Addr>,
+}
+
+/// Nothing is claimable before `cliff`, then tokens are released linearly from the start
+/// of the vesting until `duration` has passed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VestingSchedule {
+    /// cliff and duration in blocks
+    Height { cliff: u64, duration: u64 },
+    /// cliff and duration in seconds
+    Time { cliff: u64, duration: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VestingStart {
+    pub height: u64,
+    /// block time in seconds
+    pub time: u64,
+}
+
+impl VestingSchedule {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            VestingSchedule::Height { cliff, duration } => cliff <= duration,
+            VestingSchedule::Time { cliff, duration } => cliff <= duration,
+        }
+    }
+
+    pub fn vested_amount(
+        &self,
+        total: Uint128,
+        start: &VestingStart,
+        block: &BlockInfo,
+    ) -> StdResult<Uint128> {
+        let (elapsed, cliff, duration) = match self {
+            VestingSchedule::Height { cliff, duration } => {
+                (block.height.saturating_sub(start.height), cliff, duration)
+            }
+            VestingSchedule::Time { cliff, duration } => (
+                block.time.seconds().saturating_sub(start.time),
+                cliff,
+                duration,
+            ),
+        };
+
+        if elapsed < *cliff {
+            return Ok(Uint128::zero());
+        }
+
+        if elapsed >= *duration {
+            return Ok(total);
+        }
+
+        total
+            .checked_multiply_ratio(elapsed, *duration)
+            .map_err(|e| StdError::generic_err(e.to_string()))
+    }
 }
 
 impl Config {
     pub fn is_finished(&self) -> bool {
         self.finished
     }
+
+    pub fn is_started(&self, block: &BlockInfo) -> bool {
+        self.start
+            .map(|start| start.is_triggered(block))
+            .unwrap_or(true)
+    }
+
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        self.deadline.is_expired(block)
+    }
+
+    /// hard cap is reached, or the deadline has passed with funding above the soft cap
+    pub fn is_successful(&self, block: &BlockInfo) -> bool {
+        self.is_finished()
+            || (self.is_expired(block) && self.current_funding_amount >= self.soft_cap)
+    }
+
+    /// channel token amount unlocked for `funded_amount` at `block`, including claimed tokens
+    pub fn unlocked_amount(&self, funded_amount: Uint128, block: &BlockInfo) -> StdResult<Uint128> {
+        // channel token is not minted yet
+        if self.token_contract.as_str().is_empty() {
+            return Ok(Uint128::zero());
+        }
+
+        let total = self.channel_token_share(funded_amount)?;
+        match (&self.vesting, &self.vesting_start) {
+            (Some(vesting), Some(vesting_start)) => {
+                vesting.vested_amount(total, vesting_start, block)
+            }
+            (Some(_), None) => Ok(Uint128::zero()),
+            (None, _) => Ok(total),
+        }
+    }
+
+    /// funding asset amount provided as initial liquidity of the pair
+    pub fn pool_funding_amount(&self) -> Uint128 {
+        self.pool
+            .as_ref()
+            .map(|pool| self.current_funding_amount * pool.funding_ratio)
+            .unwrap_or_default()
+    }
+
+    /// channel token amount purchased with `funded_amount`
+    pub fn channel_token_share(&self, funded_amount: Uint128) -> StdResult<Uint128> {
+        self.channel_token_amount
+            .checked_multiply_ratio(funded_amount, self.current_funding_amount)
+            .map_err(|e| StdError::generic_err(e.to_string()))
+    }
+}
+
+/// Revenue is distributed to channel token holders with a reward-per-token index
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default, JsonSchema)]
+pub struct RevenueState {
+    /// accumulated revenue per channel token
+    pub reward_per_token: Decimal,
+    /// channel token balance of all tracked holders
+    pub total_balance: Uint128,
+    pub revenue_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default, JsonSchema)]
+pub struct Holder {
+    pub balance: Uint128,
+    /// `reward_per_token` at the last checkpoint
+    pub reward_per_token: Decimal,
+    /// revenue accrued until the last checkpoint
+    pub pending: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RevenueRecord {
+    pub id: u64,
+    pub amount: Uint128,
+    pub height: u64,
+    /// block time in seconds
+    pub time: u64,
+    /// `reward_per_token` after the deposit
+    pub reward_per_token: Decimal,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const FUNDING: Map<Addr, Uint128> = Map::new("funding");
+pub const WHITELIST: Map<Addr, Empty> = Map::new("whitelist");
+/// channel token amount already paid to each funder
+pub const CLAIMED: Map<Addr, Uint128> = Map::new("claimed");
+/// last funder paid by DistributeBatch
+pub const DISTRIBUTION_CURSOR: Item<Addr> = Item::new("distribution_cursor");
+pub const REVENUE_STATE: Item<RevenueState> = Item::new("revenue_state");
+pub const HOLDERS: Map<Addr, Holder> = Map::new("holders");
+pub const REVENUE_HISTORY: Map<u64, RevenueRecord> = Map::new("revenue_history");
+/// funding asset amount already sent to the recipient with TransferFund
+pub const TRANSFERRED_FUND: Item<Uint128> = Item::new("transferred_fund");
+
+#[cfg(test)]
+mod test_vesting {
+    use crate::state::{VestingSchedule, VestingStart};
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::Uint128;
+
+    #[test]
+    fn test_vested_amount() {
+        let schedule = VestingSchedule::Height {
+            cliff: 100,
+            duration: 1000,
+        };
+        let mut block = mock_env().block;
+        let start = VestingStart {
+            height: block.height,
+            time: block.time.seconds(),
+        };
+        let total = Uint128::new(10000);
+
+        let mut vested_at = |elapsed: u64| {
+            block.height = start.height + elapsed;
+            schedule.vested_amount(total, &start, &block).unwrap()
+        };
+
+        assert_eq!(vested_at(99), Uint128::zero());
+        assert_eq!(vested_at(100), Uint128::new(1000));
+        assert_eq!(vested_at(500), Uint128::new(5000));
+        assert_eq!(vested_at(1000), total);
+        assert_eq!(vested_at(5000), total);
+    }
+}
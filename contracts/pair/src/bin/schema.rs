@@ -3,7 +3,12 @@ use schemars::schema_for;
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use ysip::pair::{ExecuteMsg, InstantiateMsg, LiquidityResponse, PairInfoResponse, QueryMsg};
+use ysip::pair::{
+    AccruedProtocolFeesResponse, AmpResponse, ConfigResponse, CumulativePricesResponse,
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, LiquidityResponse, PairInfoResponse, QueryMsg,
+    ReserveDriftResponse, ReverseSimulationResponse, SimulateProvideSingleSidedResponse,
+    SimulationResponse, TwapResponse,
+};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -13,7 +18,17 @@ fn main() {
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(PairInfoResponse), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(AmpResponse), &out_dir);
+    export_schema(&schema_for!(AccruedProtocolFeesResponse), &out_dir);
+    export_schema(&schema_for!(ReserveDriftResponse), &out_dir);
     export_schema(&schema_for!(LiquidityResponse), &out_dir);
+    export_schema(&schema_for!(SimulationResponse), &out_dir);
+    export_schema(&schema_for!(ReverseSimulationResponse), &out_dir);
+    export_schema(&schema_for!(SimulateProvideSingleSidedResponse), &out_dir);
+    export_schema(&schema_for!(CumulativePricesResponse), &out_dir);
+    export_schema(&schema_for!(TwapResponse), &out_dir);
 }
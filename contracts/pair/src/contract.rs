@@ -1,22 +1,37 @@
 use crate::error::ContractError;
-use crate::math::{get_lp_fee_amount, get_protocol_fee_amount, get_swap_output_amount};
-use crate::state::{Config, Fees, Liquidity, CONFIG, LIQUIDITY};
+use crate::math::{
+    assert_max_spread, assert_slippage_tolerance, compute_offer_amount, compute_provide,
+    compute_single_sided_swap_amount, compute_swap, Curve, SwapResult,
+};
+use crate::oracle::{
+    find_observation, get_cumulative_prices, init_price_accumulators, update_price_accumulators,
+};
+use crate::state::{
+    AmpConfig, Config, Fees, Liquidity, AMP, CONFIG, LIQUIDITY, MAX_AMP, MAX_AMP_CHANGE,
+    MAX_LP_FEE_PERCENT, MAX_PROTOCOL_FEE_PERCENT, MINIMUM_LIQUIDITY, MIN_AMP_CHANGING_TIME,
+    PENDING_OWNER, PRICE_CUMULATIVE, PROTOCOL_FEES,
+};
 use cosmwasm_std::{
-    attr, entry_point, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    attr, entry_point, from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, Uint128,
+    WasmMsg,
 };
 use cw2::set_contract_version;
-use cw20::MinterResponse;
+use cw20::{Cw20ReceiveMsg, MinterResponse};
 use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
 use std::str::FromStr;
 use ysip::asset::{format_lp_token_name, Asset, AssetInfo};
 use ysip::pair::{
-    ExecuteMsg, InstantiateMsg, LiquidityResponse, PairInfo, PairInfoResponse, QueryMsg, SwapParams,
+    AccruedProtocolFeesResponse, AmpResponse, AssetDrift, ConfigResponse, CumulativePricesResponse,
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, LiquidityResponse, PairInfo, PairInfoResponse,
+    PairType, QueryMsg, ReserveDriftResponse, ReverseSimulationResponse,
+    SimulateProvideSingleSidedResponse, SimulationResponse, SwapParams, TwapResponse,
+    DEFAULT_MAX_SPREAD, MAX_ALLOWED_SLIPPAGE, MAX_ALLOWED_SPREAD,
 };
 use ysip::querier::{query_lp_token_supply, query_token_balance};
 use ysip::utils::{
-    get_bank_transfer_to_msg, get_burn_from_msg, get_cw20_mint_msg, get_cw20_transfer_from_msg,
-    get_cw20_transfer_msg, get_fee_transfer_msg,
+    get_asset_transfer_msg, get_bank_transfer_to_msg, get_burn_from_msg, get_burn_msg,
+    get_cw20_mint_msg, get_cw20_transfer_from_msg, get_cw20_transfer_msg, get_fee_transfer_msg,
 };
 
 const CONTRACT_NAME: &str = "ysip-pair-contract";
@@ -27,7 +42,7 @@ const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
 pub fn instantiate(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     msg.asset_infos[0].clone().check_is_valid(deps.api)?;
@@ -39,13 +54,43 @@ pub fn instantiate(
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+
+    match (&msg.pair_type, msg.amp) {
+        (PairType::ConstantProduct {}, None) => {}
+        (PairType::ConstantProduct {}, Some(_)) => return Err(ContractError::NotStablePair {}),
+        (PairType::Stable {}, Some(amp)) if amp > 0 && amp <= MAX_AMP => {
+            let now = env.block.time.seconds();
+            AMP.save(
+                deps.storage,
+                &AmpConfig {
+                    init_amp: amp,
+                    init_amp_time: now,
+                    next_amp: amp,
+                    next_amp_time: now,
+                },
+            )?;
+        }
+        (PairType::Stable {}, _) => return Err(ContractError::InvalidAmp { max_amp: MAX_AMP }),
+    }
+
     let config = Config {
-        pair_info: PairInfo::init(env.contract.address.clone(), msg.asset_infos.clone()),
+        owner,
+        pair_info: PairInfo::init(
+            env.contract.address.clone(),
+            msg.asset_infos.clone(),
+            msg.pair_type,
+        ),
         fees: Fees {
             protocol_fee_recipient: Addr::unchecked(msg.protocol_fee_recipient),
-            protocol_fee_percent: Decimal::from_str(&msg.protocol_fee_percent)?
-                / Decimal::from_str("100")?,
-            lp_fee_percent: Decimal::from_str(&msg.lp_fee_percent)? / Decimal::from_str("100")?,
+            protocol_fee_percent: parse_fee_percent(
+                &msg.protocol_fee_percent,
+                MAX_PROTOCOL_FEE_PERCENT,
+            )?,
+            lp_fee_percent: parse_fee_percent(&msg.lp_fee_percent, MAX_LP_FEE_PERCENT)?,
         },
     };
 
@@ -62,6 +107,11 @@ pub fn instantiate(
 
     CONFIG.save(deps.storage, &config)?;
     LIQUIDITY.save(deps.storage, &liquidity)?;
+    PROTOCOL_FEES.save(
+        deps.storage,
+        &[liquidity.token_a.clone(), liquidity.token_b.clone()],
+    )?;
+    init_price_accumulators(deps.storage, &env)?;
 
     let lp_token_name = format_lp_token_name(msg.asset_infos, &deps.querier)?;
 
@@ -84,7 +134,7 @@ pub fn instantiate(
             funds: vec![],
             label: "YSIP LP token".to_string(),
         }
-            .into(),
+        .into(),
         gas_limit: None,
         reply_on: ReplyOn::Success,
     };
@@ -118,24 +168,367 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::ProvideLiquidity { assets } => {
-            execute_provide_liquidity(deps, env, info, assets)
-        }
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::ProvideLiquidity {
+            assets,
+            slippage_tolerance,
+        } => execute_provide_liquidity(deps, env, info, assets, slippage_tolerance),
         ExecuteMsg::Swap {
             offer_asset,
             min_output_amount,
+            belief_price,
             max_spread,
             to,
-        } => execute_swap(
+        } => {
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            execute_swap(
+                deps,
+                env,
+                info,
+                SwapParams {
+                    offer_asset,
+                    min_output_amount,
+                    belief_price,
+                    max_spread,
+                    to: to_addr,
+                },
+            )
+        }
+        ExecuteMsg::RemoveLiquidity { amount } => {
+            execute_remove_liquidity(deps, env, info, amount, None, None)
+        }
+        ExecuteMsg::WithdrawLiquidity {
+            amount,
+            receive_asset,
+            min_out,
+        } => execute_remove_liquidity(deps, env, info, amount, receive_asset, min_out),
+        ExecuteMsg::ProvideSingleSided { asset, min_lp_out } => {
+            execute_provide_single_sided(deps, env, info, asset, min_lp_out)
+        }
+        ExecuteMsg::UpdateFees {
+            protocol_fee_percent,
+            lp_fee_percent,
+            protocol_fee_recipient,
+        } => execute_update_fees(
             deps,
-            env,
             info,
-            offer_asset,
+            protocol_fee_percent,
+            lp_fee_percent,
+            protocol_fee_recipient,
+        ),
+        ExecuteMsg::ProposeNewOwner { owner } => execute_propose_new_owner(deps, info, owner),
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, info),
+        ExecuteMsg::CollectProtocolFees {} => execute_collect_protocol_fees(deps, info),
+        ExecuteMsg::Sync {} => execute_sync(deps, env),
+        ExecuteMsg::Skim { to } => execute_skim(deps, env, info, to),
+        ExecuteMsg::StartChangingAmp {
+            next_amp,
+            next_amp_time,
+        } => execute_start_changing_amp(deps, env, info, next_amp, next_amp_time),
+        ExecuteMsg::StopChangingAmp {} => execute_stop_changing_amp(deps, env, info),
+    }
+}
+
+/// Parses a fee given in percent into a ratio, e.g. "0.3" into 0.003
+fn parse_fee_percent(fee_percent: &str, max_percent: u64) -> Result<Decimal, ContractError> {
+    let fee_percent = Decimal::from_str(fee_percent)?;
+    if fee_percent > Decimal::from_ratio(max_percent, 1u64) {
+        return Err(ContractError::FeeTooHigh { max_percent });
+    }
+
+    Ok(fee_percent / Decimal::from_ratio(100u64, 1u64))
+}
+
+/// Parses a max spread given in percent into a ratio, `DEFAULT_MAX_SPREAD` if not set
+fn parse_max_spread(max_spread: Option<&str>) -> Result<Decimal, ContractError> {
+    let max_spread = match max_spread {
+        Some(max_spread) => Decimal::from_str(max_spread)?,
+        None => Decimal::from_ratio(DEFAULT_MAX_SPREAD, 1u64),
+    };
+    if max_spread > Decimal::from_ratio(MAX_ALLOWED_SPREAD, 1u64) {
+        return Err(ContractError::AllowedSpreadAssertion {
+            max_percent: MAX_ALLOWED_SPREAD,
+        });
+    }
+
+    Ok(max_spread / Decimal::from_ratio(100u64, 1u64))
+}
+
+fn execute_update_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    protocol_fee_percent: Option<String>,
+    lp_fee_percent: Option<String>,
+    protocol_fee_recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(protocol_fee_percent) = protocol_fee_percent {
+        config.fees.protocol_fee_percent =
+            parse_fee_percent(&protocol_fee_percent, MAX_PROTOCOL_FEE_PERCENT)?;
+    }
+
+    if let Some(lp_fee_percent) = lp_fee_percent {
+        config.fees.lp_fee_percent = parse_fee_percent(&lp_fee_percent, MAX_LP_FEE_PERCENT)?;
+    }
+
+    if let Some(protocol_fee_recipient) = protocol_fee_recipient {
+        config.fees.protocol_fee_recipient = deps.api.addr_validate(&protocol_fee_recipient)?;
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_fees"),
+        attr(
+            "protocol_fee_percent",
+            config.fees.protocol_fee_percent.to_string(),
+        ),
+        attr("lp_fee_percent", config.fees.lp_fee_percent.to_string()),
+        attr("protocol_fee_recipient", config.fees.protocol_fee_recipient),
+    ]))
+}
+
+fn execute_propose_new_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_owner = deps.api.addr_validate(&owner)?;
+    PENDING_OWNER.save(deps.storage, &new_owner)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_new_owner")
+        .add_attribute("new_owner", new_owner))
+}
+
+fn execute_accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let new_owner = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoOwnershipProposal {})?;
+    if info.sender != new_owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let previous_owner = config.owner;
+    config.owner = new_owner;
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("previous_owner", previous_owner)
+        .add_attribute("new_owner", config.owner))
+}
+
+fn execute_start_changing_amp(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    next_amp: u64,
+    next_amp_time: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let amp_config = AMP
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NotStablePair {})?;
+
+    if next_amp == 0 || next_amp > MAX_AMP {
+        return Err(ContractError::InvalidAmp { max_amp: MAX_AMP });
+    }
+
+    let now = env.block.time.seconds();
+    if now < amp_config.init_amp_time + MIN_AMP_CHANGING_TIME
+        || next_amp_time < now + MIN_AMP_CHANGING_TIME
+    {
+        return Err(ContractError::MinAmpChangingTimeAssertion {
+            min_seconds: MIN_AMP_CHANGING_TIME,
+        });
+    }
+
+    let current_amp = amp_config.current_amp(now);
+    if next_amp > current_amp * MAX_AMP_CHANGE || next_amp * MAX_AMP_CHANGE < current_amp {
+        return Err(ContractError::MaxAmpChangeAssertion {
+            max_change: MAX_AMP_CHANGE,
+        });
+    }
+
+    AMP.save(
+        deps.storage,
+        &AmpConfig {
+            init_amp: current_amp,
+            init_amp_time: now,
+            next_amp,
+            next_amp_time,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "start_changing_amp")
+        .add_attribute("init_amp", current_amp.to_string())
+        .add_attribute("next_amp", next_amp.to_string())
+        .add_attribute("next_amp_time", next_amp_time.to_string()))
+}
+
+fn execute_stop_changing_amp(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let amp_config = AMP
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NotStablePair {})?;
+
+    let now = env.block.time.seconds();
+    let current_amp = amp_config.current_amp(now);
+    AMP.save(
+        deps.storage,
+        &AmpConfig {
+            init_amp: current_amp,
+            init_amp_time: now,
+            next_amp: current_amp,
+            next_amp_time: now,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "stop_changing_amp")
+        .add_attribute("amp", current_amp.to_string()))
+}
+
+/// Curve of the pool at the current block
+fn get_curve(storage: &dyn Storage, config: &Config, env: &Env) -> StdResult<Curve> {
+    match config.pair_info.pair_type {
+        PairType::ConstantProduct {} => Ok(Curve::ConstantProduct),
+        PairType::Stable {} => Ok(Curve::Stable {
+            amp: AMP.load(storage)?.current_amp(env.block.time.seconds()),
+        }),
+    }
+}
+
+fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+
+    let sent_asset = Asset {
+        info: AssetInfo::Token {
+            contract_addr: info.sender.clone(),
+        },
+        amount: cw20_msg.amount,
+    };
+
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::Swap {
             min_output_amount,
+            belief_price,
             max_spread,
             to,
-        ),
-        ExecuteMsg::RemoveLiquidity { amount } => execute_remove_liquidity(deps, env, info, amount),
+        } => {
+            if !config.pair_info.asset_infos.contains(&sent_asset.info) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                sender,
+                SwapParams {
+                    offer_asset: sent_asset,
+                    min_output_amount,
+                    belief_price,
+                    max_spread,
+                    to: to_addr,
+                },
+            )
+        }
+        Cw20HookMsg::ProvideLiquidity {
+            assets,
+            slippage_tolerance,
+        } => {
+            if !config.pair_info.asset_infos.contains(&sent_asset.info) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            if !assets.contains(&sent_asset) {
+                return Err(ContractError::AssetMismatch {});
+            }
+
+            for asset in &assets {
+                if !asset.is_cw20_token() && !asset.amount.is_zero() {
+                    return Err(ContractError::NativeTokenInHook {});
+                }
+            }
+
+            provide_liquidity(
+                deps,
+                env,
+                sender,
+                assets,
+                slippage_tolerance,
+                Some(info.sender),
+            )
+        }
+        Cw20HookMsg::ProvideSingleSided { min_lp_out } => {
+            if !config.pair_info.asset_infos.contains(&sent_asset.info) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            provide_single_sided(deps, env, sender, sent_asset, min_lp_out)
+        }
+        Cw20HookMsg::WithdrawLiquidity {
+            receive_asset,
+            min_out,
+        } => {
+            if info.sender != config.pair_info.liquidity_token {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let lp_token_burn_msg = get_burn_msg(&info.sender, cw20_msg.amount)?;
+
+            withdraw_liquidity(
+                deps,
+                env,
+                sender,
+                cw20_msg.amount,
+                lp_token_burn_msg,
+                receive_asset,
+                min_out,
+            )
+        }
     }
 }
 
@@ -143,195 +536,160 @@ fn get_reserve(deps: Deps, assets: [Asset; 2]) -> StdResult<[Asset; 2]> {
     let reserve = LIQUIDITY.load(deps.storage)?;
 
     let token1_reserve = [&reserve.token_a, &reserve.token_b]
-        .iter()
+        .into_iter()
         .find(|i| i.info.eq(&assets[0].info))
         .expect("reserve not found")
         .clone();
 
     let token2_reserve = [&reserve.token_a, &reserve.token_b]
-        .iter()
+        .into_iter()
         .find(|i| i.info.eq(&assets[1].info))
         .expect("reserve not found")
         .clone();
 
-    Ok([token1_reserve.clone(), token2_reserve.clone()])
+    Ok([token1_reserve, token2_reserve])
 }
 
-fn execute_swap(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    offer_asset: Asset,
-    min_output_amount: Option<String>,
-    max_spread: Option<String>,
-    to: Option<String>,
-) -> Result<Response, ContractError> {
-    let to_addr = if let Some(to_addr) = to {
-        Some(deps.api.addr_validate(to_addr.as_str())?)
+/// Returns the tracked reserves of the offer side and the ask side of the pool
+fn get_offer_ask_reserves(
+    deps: Deps,
+    config: &Config,
+    offer_info: &AssetInfo,
+) -> Result<(Asset, Asset), ContractError> {
+    let ask_info = if offer_info.eq(&config.pair_info.asset_infos[0]) {
+        config.pair_info.asset_infos[1].clone()
+    } else if offer_info.eq(&config.pair_info.asset_infos[1]) {
+        config.pair_info.asset_infos[0].clone()
     } else {
-        None
+        return Err(ContractError::AssetMismatch {});
     };
 
-    swap(
+    let [offer_reserve, ask_reserve] = get_reserve(
         deps,
-        env,
-        info,
-        SwapParams {
-            offer_asset,
-            min_output_amount,
-            max_spread,
-            to: to_addr,
-        },
-    )
+        [
+            Asset {
+                info: offer_info.clone(),
+                amount: Uint128::zero(),
+            },
+            Asset {
+                info: ask_info,
+                amount: Uint128::zero(),
+            },
+        ],
+    )?;
+
+    Ok((offer_reserve, ask_reserve))
 }
 
-fn swap(
+fn execute_swap(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     params: SwapParams,
 ) -> Result<Response, ContractError> {
-    params.offer_asset.assert_sent_native_token_balance(&info)?;
-    let config = CONFIG.load(deps.storage)?;
-
-    let pools: [Asset; 2] = config
-        .pair_info
-        .query_pools(&deps.querier, &env.contract.address)?;
-
-    let offer_pool: Asset;
-    let ask_pool: Asset;
-
-    if params.offer_asset.info.eq(&pools[0].info) {
-        offer_pool = pools[0].clone();
-        ask_pool = pools[1].clone();
-    } else if params.offer_asset.info.eq(&pools[1].info) {
-        offer_pool = pools[1].clone();
-        ask_pool = pools[0].clone();
-    } else {
-        return Err(ContractError::AssetMismatch {});
+    // CW20 offers have to be sent through the Receive hook
+    if params.offer_asset.is_cw20_token() {
+        return Err(ContractError::Cw20DirectSwap {});
     }
 
-    let reserves = get_reserve(deps.as_ref(), pools.clone())?;
-
-    let offer_pool_reserve = reserves
-        .iter()
-        .find(|a| a.info == offer_pool.info)
-        .expect("reserve not found");
+    params.offer_asset.assert_sent_native_token_balance(&info)?;
 
-    let ask_pool_reserve = reserves
-        .iter()
-        .find(|a| a.info == ask_pool.info)
-        .expect("reserve not found");
+    swap(deps, env, info.sender, params)
+}
 
-    let fees = config.fees;
+fn swap(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    params: SwapParams,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
 
-    let protocol_fee_amount =
-        get_protocol_fee_amount(params.offer_asset.amount, fees.protocol_fee_percent)?;
+    let (offer_pool, ask_pool) =
+        get_offer_ask_reserves(deps.as_ref(), &config, &params.offer_asset.info)?;
 
-    let token_bought_amount = get_swap_output_amount(
-        params.offer_asset.amount - protocol_fee_amount,
-        offer_pool_reserve.amount,
-        ask_pool_reserve.amount,
-        fees.lp_fee_percent,
-    )?;
+    let curve = get_curve(deps.storage, &config, &env)?;
+    let fees = config.fees;
 
-    let (input_token_fee_amount, output_token_fee_amount) = get_lp_fee_amount(
-        params.offer_asset.amount - protocol_fee_amount,
-        token_bought_amount,
-        fees.lp_fee_percent,
+    let SwapResult {
+        protocol_fee_amount,
+        input_token_fee_amount,
+        output_token_fee_amount,
+        net_input_amount,
+        net_token_output_amount,
+        spread_amount,
+    } = compute_swap(
+        params.offer_asset.amount,
+        offer_pool.amount,
+        ask_pool.amount,
+        &fees,
+        curve,
     )?;
 
-    // amount of token into the pool
-    let net_input_amount = params.offer_asset.amount - protocol_fee_amount - input_token_fee_amount;
-
-    //amount of token out of the pool
-    let net_token_output_amount = token_bought_amount - output_token_fee_amount;
+    if let Some(min_output_amount) = &params.min_output_amount {
+        let min_output_amount = Uint128::from_str(min_output_amount)?;
+        if net_token_output_amount < min_output_amount {
+            return Err(ContractError::MinOutputAssertion {
+                min_output_amount,
+                return_amount: net_token_output_amount,
+            });
+        }
+    }
 
-    params.assert_min_token_bought(net_token_output_amount)?;
+    let belief_price = params
+        .belief_price
+        .as_deref()
+        .map(Decimal::from_str)
+        .transpose()?;
+    assert_max_spread(
+        belief_price,
+        parse_max_spread(params.max_spread.as_deref())?,
+        params.offer_asset.amount,
+        net_token_output_amount,
+        spread_amount,
+    )?;
 
     let mut msgs = vec![];
 
-    // transfer token from owner to pair contract
-    if let AssetInfo::Token { contract_addr } = offer_pool.info.clone() {
-        let msg = get_cw20_transfer_from_msg(
-            &info.sender,
-            &env.contract.address,
-            &contract_addr,
-            params.offer_asset.amount,
-        )?;
-        msgs.push(msg)
-    }
+    let receiver = params.to.clone().unwrap_or_else(|| sender.clone());
 
-    // send output token or coin to sender
+    // send output token or coin to receiver
     match ask_pool.info.clone() {
         AssetInfo::Token { contract_addr } => msgs.push(get_cw20_transfer_msg(
-            &info.sender,
+            &receiver,
             &contract_addr,
             net_token_output_amount,
         )?),
         AssetInfo::NativeToken { denom } => msgs.push(get_bank_transfer_to_msg(
-            &info.sender.clone(),
+            &receiver,
             &denom,
             net_token_output_amount,
         )),
     };
 
-    if let AssetInfo::NativeToken { denom } = params.offer_asset.info {
-        if denom == "ukrw" {
-            msgs.push(get_fee_transfer_msg(
-                &fees.protocol_fee_recipient,
-                Asset {
-                    info: offer_pool.info.clone(),
-                    amount: protocol_fee_amount,
-                },
-            )?);
-        }
-    };
-
-    println!(
-        "liquidity before swap: {:?}",
-        LIQUIDITY.load(deps.storage).unwrap()
-    );
-
-    LIQUIDITY.update(deps.storage, |mut liquidity| -> Result<_, ContractError> {
-        if liquidity.token_a.info == offer_pool.info && liquidity.token_b.info == ask_pool.info {
-            liquidity.token_a.amount = liquidity
-                .token_a
-                .amount
-                .checked_add(net_input_amount + input_token_fee_amount)
-                .map_err(StdError::overflow)?;
-            liquidity.token_b.amount = liquidity
-                .token_b
-                .amount
-                .checked_sub(net_token_output_amount)
-                .map_err(StdError::overflow)?;
-        } else if liquidity.token_b.info == offer_pool.info
-            && liquidity.token_a.info == ask_pool.info
-        {
-            liquidity.token_b.amount = liquidity
-                .token_b
-                .amount
-                .checked_add(net_input_amount + input_token_fee_amount)
-                .map_err(StdError::overflow)?;
-            liquidity.token_a.amount = liquidity
-                .token_a
-                .amount
-                .checked_sub(net_token_output_amount)
-                .map_err(StdError::overflow)?;
-        }
-        Ok(liquidity)
-    })?;
-
-    println!(
-        "liquidity after swap: {:?}",
-        LIQUIDITY.load(deps.storage).unwrap()
-    );
+    apply_swap(
+        deps.storage,
+        &env,
+        &offer_pool.info,
+        curve,
+        &SwapResult {
+            protocol_fee_amount,
+            input_token_fee_amount,
+            output_token_fee_amount,
+            net_input_amount,
+            net_token_output_amount,
+            spread_amount,
+        },
+    )?;
 
     Ok(Response::new()
         .add_attributes(vec![
             attr("action", "swap"),
+            attr("sender", &sender),
+            attr("receiver", &receiver),
             attr("token_in_amount", params.offer_asset.amount),
             attr("token_out_amount", net_token_output_amount),
+            attr("spread_amount", spread_amount),
             attr("protocol_fee_amount", protocol_fee_amount),
             attr("protocol_fee_recipient", &fees.protocol_fee_recipient),
             attr("input_token_fee_amount", input_token_fee_amount),
@@ -340,44 +698,178 @@ fn swap(
         .add_messages(msgs))
 }
 
-fn get_lp_token_amount_to_mint(
-    token1_amount: Uint128,
-    liquidity_supply: Uint128,
-    token1_reserve: Uint128,
-) -> Result<Uint128, ContractError> {
-    if liquidity_supply == Uint128::zero() {
-        Ok(token1_amount)
-    } else {
-        Ok(token1_amount
-            .checked_mul(liquidity_supply)
-            .map_err(StdError::overflow)?
-            .checked_div(token1_reserve)
-            .map_err(StdError::divide_by_zero)?)
-    }
-}
-
-fn get_token2_amount_required(
-    max_token: Uint128,
-    token1_amount: Uint128,
-    liquidity_supply: Uint128,
-    token2_reserve: Uint128,
-    token1_reserve: Uint128,
-) -> Result<Uint128, StdError> {
-    if liquidity_supply == Uint128::zero() {
-        Ok(max_token)
-    } else {
-        let temp_token2_amount = token1_amount
-            .checked_mul(token2_reserve)
-            .map_err(StdError::overflow)?
-            .checked_div(token1_reserve)
-            .map_err(StdError::divide_by_zero)?;
-
-        if temp_token2_amount.eq(&Uint128::zero()) {
-            Ok(Uint128::one())
+/// Accrues the protocol fee and moves the reserves by the swap of `offer_info`
+fn apply_swap(
+    storage: &mut dyn Storage,
+    env: &Env,
+    offer_info: &AssetInfo,
+    curve: Curve,
+    result: &SwapResult,
+) -> Result<(), ContractError> {
+    // protocol fee stays in the pair until the recipient collects it
+    PROTOCOL_FEES.update(storage, |mut protocol_fees| -> StdResult<_> {
+        for protocol_fee in protocol_fees.iter_mut() {
+            if protocol_fee.info == *offer_info {
+                protocol_fee.amount = protocol_fee
+                    .amount
+                    .checked_add(result.protocol_fee_amount)?;
+            }
+        }
+        Ok(protocol_fees)
+    })?;
+
+    let liquidity = LIQUIDITY.load(storage)?;
+    update_price_accumulators(storage, env, &liquidity, curve)?;
+
+    LIQUIDITY.update(storage, |mut liquidity| -> Result<_, ContractError> {
+        let (offer_reserve, ask_reserve) = if liquidity.token_a.info == *offer_info {
+            (&mut liquidity.token_a, &mut liquidity.token_b)
         } else {
-            Ok(temp_token2_amount)
+            (&mut liquidity.token_b, &mut liquidity.token_a)
+        };
+
+        offer_reserve.amount = offer_reserve
+            .amount
+            .checked_add(result.net_input_amount + result.input_token_fee_amount)
+            .map_err(StdError::overflow)?;
+        ask_reserve.amount = ask_reserve
+            .amount
+            .checked_sub(result.net_token_output_amount)
+            .map_err(StdError::overflow)?;
+
+        Ok(liquidity)
+    })?;
+
+    Ok(())
+}
+
+fn execute_collect_protocol_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.fees.protocol_fee_recipient {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let protocol_fees = PROTOCOL_FEES.load(deps.storage)?;
+
+    let mut msgs = vec![];
+    let mut attrs = vec![attr("action", "collect_protocol_fees")];
+    for protocol_fee in protocol_fees.iter() {
+        if protocol_fee.amount.is_zero() {
+            continue;
+        }
+
+        attrs.push(attr(protocol_fee.info.to_string(), protocol_fee.amount));
+        msgs.push(get_fee_transfer_msg(
+            &config.fees.protocol_fee_recipient,
+            protocol_fee.clone(),
+        )?);
+    }
+
+    PROTOCOL_FEES.save(
+        deps.storage,
+        &protocol_fees.map(|protocol_fee| Asset {
+            info: protocol_fee.info,
+            amount: Uint128::zero(),
+        }),
+    )?;
+
+    Ok(Response::new().add_attributes(attrs).add_messages(msgs))
+}
+
+/// Compares the tracked reserves and accrued protocol fees with the actual balances
+fn get_reserve_drift(deps: Deps, env: &Env) -> StdResult<[AssetDrift; 2]> {
+    let liquidity = LIQUIDITY.load(deps.storage)?;
+    let protocol_fees = PROTOCOL_FEES.load(deps.storage)?;
+
+    let drift = |reserve: Asset| -> StdResult<AssetDrift> {
+        let protocol_fee = protocol_fees
+            .iter()
+            .find(|protocol_fee| protocol_fee.info == reserve.info)
+            .map(|protocol_fee| protocol_fee.amount)
+            .unwrap_or_default();
+        let balance = reserve
+            .info
+            .query_pool(&deps.querier, &env.contract.address)?;
+        let expected = reserve.amount.checked_add(protocol_fee)?;
+
+        Ok(AssetDrift {
+            info: reserve.info,
+            reserve: reserve.amount,
+            protocol_fee,
+            balance,
+            excess: balance.saturating_sub(expected),
+            deficit: expected.saturating_sub(balance),
+        })
+    };
+
+    Ok([drift(liquidity.token_a)?, drift(liquidity.token_b)?])
+}
+
+fn execute_sync(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let [drift_a, drift_b] = get_reserve_drift(deps.as_ref(), &env)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let liquidity = LIQUIDITY.load(deps.storage)?;
+    let curve = get_curve(deps.storage, &config, &env)?;
+    update_price_accumulators(deps.storage, &env, &liquidity, curve)?;
+
+    let reserve_a = drift_a.balance.saturating_sub(drift_a.protocol_fee);
+    let reserve_b = drift_b.balance.saturating_sub(drift_b.protocol_fee);
+    LIQUIDITY.save(
+        deps.storage,
+        &Liquidity {
+            token_a: Asset {
+                info: drift_a.info,
+                amount: reserve_a,
+            },
+            token_b: Asset {
+                info: drift_b.info,
+                amount: reserve_b,
+            },
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "sync"),
+        attr("token1_reserve", reserve_a),
+        attr("token2_reserve", reserve_b),
+    ]))
+}
+
+fn execute_skim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let to = deps.api.addr_validate(&to)?;
+
+    let mut msgs = vec![];
+    let mut attrs = vec![attr("action", "skim"), attr("to", &to)];
+    for drift in get_reserve_drift(deps.as_ref(), &env)? {
+        if drift.excess.is_zero() {
+            continue;
         }
+
+        attrs.push(attr(drift.info.to_string(), drift.excess));
+        msgs.push(get_asset_transfer_msg(
+            &to,
+            Asset {
+                info: drift.info,
+                amount: drift.excess,
+            },
+        )?);
     }
+
+    Ok(Response::new().add_attributes(attrs).add_messages(msgs))
 }
 
 fn execute_provide_liquidity(
@@ -385,140 +877,351 @@ fn execute_provide_liquidity(
     env: Env,
     info: MessageInfo,
     assets: [Asset; 2],
+    slippage_tolerance: Option<String>,
 ) -> Result<Response, ContractError> {
-    assets[0].info.check_is_valid(deps.api)?;
-    assets[1].info.check_is_valid(deps.api)?;
-
     for asset in &assets {
         asset.assert_sent_native_token_balance(&info)?;
     }
 
-    let config = CONFIG.load(deps.storage)?;
+    provide_liquidity(deps, env, info.sender, assets, slippage_tolerance, None)
+}
 
-    let pools: [Asset; 2] = config
-        .pair_info
-        .query_pools(&deps.querier, &env.contract.address)?;
+/// Parses a slippage tolerance given in percent into a ratio
+fn parse_slippage_tolerance(slippage_tolerance: &str) -> Result<Decimal, ContractError> {
+    let slippage_tolerance = Decimal::from_str(slippage_tolerance)?;
+    if slippage_tolerance > Decimal::from_ratio(MAX_ALLOWED_SLIPPAGE, 1u64) {
+        return Err(ContractError::AllowedSlippageAssertion {
+            max_percent: MAX_ALLOWED_SLIPPAGE,
+        });
+    }
 
-    let deposits: [Uint128; 2] = [
-        assets
-            .iter()
-            .find(|a| a.info.eq(&pools[0].info))
-            .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-        assets
+    Ok(slippage_tolerance / Decimal::from_ratio(100u64, 1u64))
+}
+
+/// `received_token` is the CW20 contract whose tokens were already sent to the pair
+/// through the Receive hook, so it is not pulled with TransferFrom
+fn provide_liquidity(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<String>,
+    received_token: Option<Addr>,
+) -> Result<Response, ContractError> {
+    assets[0].info.check_is_valid(deps.api)?;
+    assets[1].info.check_is_valid(deps.api)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let liquidity = LIQUIDITY.load(deps.storage)?;
+    let pools = [liquidity.token_a.clone(), liquidity.token_b.clone()];
+
+    // deposits in the order of the pool, whatever the order of `assets`
+    let mut deposits = [Uint128::zero(); 2];
+    for (deposit, pool) in deposits.iter_mut().zip(pools.iter()) {
+        *deposit = assets
             .iter()
-            .find(|a| a.info.eq(&pools[1].info))
+            .find(|a| a.info.eq(&pool.info))
             .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-    ];
+            .ok_or(ContractError::AssetMismatch {})?;
+    }
 
-    if deposits[0].is_zero() && deposits[1].is_zero() {
+    if deposits.iter().any(|deposit| deposit.is_zero()) {
         return Err(ContractError::InvalidZeroAmount {});
     }
 
-    let [token1_reserve, token2_reserve] = get_reserve(deps.as_ref(), assets.clone())?;
-
+    let reserves = [pools[0].amount, pools[1].amount];
     let lp_token_supply =
         query_lp_token_supply(&deps.as_ref().querier, &config.pair_info.liquidity_token)?;
 
-    let liquidity_amount =
-        get_lp_token_amount_to_mint(deposits[0], lp_token_supply, token1_reserve.amount)?;
-
-    let token2_amount = get_token2_amount_required(
-        deposits[1],
-        deposits[0],
-        lp_token_supply,
-        token2_reserve.amount,
-        token1_reserve.amount,
-    )?;
-
-    if deposits[1] < token2_amount {
-        return Err(ContractError::NotEnoughTokenAmount {
-            need: token2_amount,
-            supplied: deposits[1],
-        });
+    if let Some(slippage_tolerance) = slippage_tolerance {
+        if !lp_token_supply.is_zero() {
+            assert_slippage_tolerance(
+                parse_slippage_tolerance(&slippage_tolerance)?,
+                deposits,
+                reserves,
+            )?;
+        }
     }
 
-    let mut transfer_msgs: Vec<CosmosMsg> = vec![];
-    if let AssetInfo::Token { contract_addr } = assets[0].clone().info {
-        transfer_msgs.push(get_cw20_transfer_from_msg(
-            &info.sender,
-            &env.contract.address,
-            &contract_addr,
-            deposits[0],
-        )?)
-    }
+    let (share, used) = compute_provide(deposits, reserves, lp_token_supply)?;
 
-    if let AssetInfo::Token { contract_addr } = assets[1].clone().info {
-        transfer_msgs.push(get_cw20_transfer_from_msg(
-            &info.sender,
-            &env.contract.address,
-            &contract_addr,
-            token2_amount,
-        )?)
-    }
-
-    // refund needed
-    if deposits[1] > token2_amount {
-        if let AssetInfo::NativeToken { denom } = assets[1].clone().info {
-            transfer_msgs.push(get_bank_transfer_to_msg(
-                &info.sender,
-                &denom,
-                deposits[1] - token2_amount,
-            ))
+    let mut transfer_msgs: Vec<CosmosMsg> = vec![];
+    for ((pool, deposit), used) in pools.iter().zip(deposits).zip(used) {
+        let refund = deposit - used;
+        match &pool.info {
+            AssetInfo::Token { contract_addr } => {
+                if received_token.as_ref() == Some(contract_addr) {
+                    if !refund.is_zero() {
+                        transfer_msgs.push(get_cw20_transfer_msg(&sender, contract_addr, refund)?);
+                    }
+                } else if !used.is_zero() {
+                    // only the used amount is pulled, so nothing has to be refunded
+                    transfer_msgs.push(get_cw20_transfer_from_msg(
+                        &sender,
+                        &env.contract.address,
+                        contract_addr,
+                        used,
+                    )?);
+                }
+            }
+            AssetInfo::NativeToken { denom } => {
+                if !refund.is_zero() {
+                    transfer_msgs.push(get_bank_transfer_to_msg(&sender, denom, refund));
+                }
+            }
         }
     }
 
-    LIQUIDITY.update(deps.storage, |mut liq| -> Result<_, ContractError> {
-        assets
-            .iter()
-            .find(|a| a.info.eq(&liq.token_a.info))
-            .map(|_| {
-                liq.token_a.amount = liq
-                    .token_a
-                    .amount
-                    .checked_add(deposits[0])
-                    .expect("overflow");
-            });
+    let curve = get_curve(deps.storage, &config, &env)?;
+    update_price_accumulators(deps.storage, &env, &liquidity, curve)?;
 
-        assets
-            .iter()
-            .find(|a| a.info.eq(&liq.token_b.info))
-            .map(|_| {
-                liq.token_b.amount = liq
-                    .token_b
-                    .amount
-                    .checked_add(token2_amount)
-                    .expect("overflow");
-            });
+    LIQUIDITY.update(deps.storage, |mut liq| -> Result<_, ContractError> {
+        liq.token_a.amount = liq
+            .token_a
+            .amount
+            .checked_add(used[0])
+            .map_err(StdError::overflow)?;
+        liq.token_b.amount = liq
+            .token_b
+            .amount
+            .checked_add(used[1])
+            .map_err(StdError::overflow)?;
 
         Ok(liq)
     })?;
 
-    let mint_lp_tokens_msg = get_cw20_mint_msg(
-        &info.sender,
+    let mut mint_lp_tokens_msgs = vec![];
+    let mut liquidity_amount = share;
+    if lp_token_supply.is_zero() {
+        if liquidity_amount <= MINIMUM_LIQUIDITY {
+            return Err(ContractError::MinimumLiquidityAmount {
+                min_lp: MINIMUM_LIQUIDITY,
+                given: liquidity_amount,
+            });
+        }
+
+        // locked in the pair forever, so the lp supply never drops back to zero
+        mint_lp_tokens_msgs.push(get_cw20_mint_msg(
+            &env.contract.address,
+            MINIMUM_LIQUIDITY,
+            &config.pair_info.liquidity_token,
+        )?);
+        liquidity_amount -= MINIMUM_LIQUIDITY;
+    } else if liquidity_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    mint_lp_tokens_msgs.push(get_cw20_mint_msg(
+        &sender,
         liquidity_amount,
         &config.pair_info.liquidity_token,
-    )?;
+    )?);
 
     Ok(Response::new()
         .add_attribute("action", "provide_liquidity")
-        .add_attribute("token_1_amount", deposits[0])
-        .add_attribute("token_2_amount", token2_amount)
+        .add_attribute("token_1_amount", used[0])
+        .add_attribute("token_2_amount", used[1])
+        .add_attribute("token_1_refund", deposits[0] - used[0])
+        .add_attribute("token_2_refund", deposits[1] - used[1])
+        .add_attribute("liquidity_amount", liquidity_amount)
         .add_messages(transfer_msgs)
-        .add_message(mint_lp_tokens_msg))
+        .add_messages(mint_lp_tokens_msgs))
+}
+
+/// Single sided deposit split into a swap to the ask side and a deposit of both sides
+struct SingleSidedProvide {
+    offer_info: AssetInfo,
+    ask_info: AssetInfo,
+    swap_amount: Uint128,
+    swap_result: SwapResult,
+    /// offer side and ask side deposited after the swap
+    deposits: [Uint128; 2],
+    /// offer side and ask side actually used by the deposit
+    used: [Uint128; 2],
+    share: Uint128,
+}
+
+fn compute_provide_single_sided(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    offer_asset: &Asset,
+) -> Result<SingleSidedProvide, ContractError> {
+    if offer_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let (offer_pool, ask_pool) = get_offer_ask_reserves(deps, config, &offer_asset.info)?;
+
+    // the pool ratio is only set by a regular deposit
+    let lp_token_supply = query_lp_token_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    if lp_token_supply.is_zero() {
+        return Err(ContractError::NotEnoughLiquidity {});
+    }
+
+    let curve = get_curve(deps.storage, config, env)?;
+    let swap_amount = compute_single_sided_swap_amount(
+        offer_asset.amount,
+        offer_pool.amount,
+        ask_pool.amount,
+        &config.fees,
+        curve,
+    )?;
+    let swap_result = compute_swap(
+        swap_amount,
+        offer_pool.amount,
+        ask_pool.amount,
+        &config.fees,
+        curve,
+    )?;
+
+    let reserves = [
+        offer_pool.amount + swap_result.net_input_amount + swap_result.input_token_fee_amount,
+        ask_pool.amount - swap_result.net_token_output_amount,
+    ];
+    let deposits = [
+        offer_asset.amount - swap_amount,
+        swap_result.net_token_output_amount,
+    ];
+    let (share, used) = compute_provide(deposits, reserves, lp_token_supply)?;
+
+    Ok(SingleSidedProvide {
+        offer_info: offer_pool.info,
+        ask_info: ask_pool.info,
+        swap_amount,
+        swap_result,
+        deposits,
+        used,
+        share,
+    })
+}
+
+fn execute_provide_single_sided(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    min_lp_out: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    // CW20 deposits have to be sent through the Receive hook
+    if asset.is_cw20_token() {
+        return Err(ContractError::Cw20DirectSwap {});
+    }
+
+    asset.assert_sent_native_token_balance(&info)?;
+
+    provide_single_sided(deps, env, info.sender, asset, min_lp_out)
+}
+
+/// `asset` is already held by the pair
+fn provide_single_sided(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    asset: Asset,
+    min_lp_out: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let SingleSidedProvide {
+        offer_info,
+        ask_info,
+        swap_amount,
+        swap_result,
+        deposits,
+        used,
+        share,
+    } = compute_provide_single_sided(deps.as_ref(), &env, &config, &asset)?;
+
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    match min_lp_out {
+        Some(min_lp_out) => {
+            if share < min_lp_out {
+                return Err(ContractError::MinLpOutAssertion {
+                    min_lp_out,
+                    lp_amount: share,
+                });
+            }
+        }
+        // without min_lp_out the internal swap is bounded like a swap without max_spread
+        None => assert_max_spread(
+            None,
+            parse_max_spread(None)?,
+            swap_amount,
+            swap_result.net_token_output_amount,
+            swap_result.spread_amount,
+        )?,
+    }
+
+    let curve = get_curve(deps.storage, &config, &env)?;
+    apply_swap(deps.storage, &env, &offer_info, curve, &swap_result)?;
+
+    LIQUIDITY.update(deps.storage, |mut liquidity| -> Result<_, ContractError> {
+        for reserve in [&mut liquidity.token_a, &mut liquidity.token_b] {
+            let used = if reserve.info == offer_info {
+                used[0]
+            } else {
+                used[1]
+            };
+            reserve.amount = reserve
+                .amount
+                .checked_add(used)
+                .map_err(StdError::overflow)?;
+        }
+
+        Ok(liquidity)
+    })?;
+
+    let refunds = [
+        Asset {
+            info: offer_info,
+            amount: deposits[0] - used[0],
+        },
+        Asset {
+            info: ask_info,
+            amount: deposits[1] - used[1],
+        },
+    ];
+
+    let mut msgs = vec![];
+    for refund in &refunds {
+        if !refund.amount.is_zero() {
+            msgs.push(get_asset_transfer_msg(&sender, refund.clone())?);
+        }
+    }
+
+    msgs.push(get_cw20_mint_msg(
+        &sender,
+        share,
+        &config.pair_info.liquidity_token,
+    )?);
+
+    Ok(Response::new()
+        .add_attribute("action", "provide_single_sided")
+        .add_attribute("sender", &sender)
+        .add_attribute("offer_amount", asset.amount)
+        .add_attribute("swap_amount", swap_amount)
+        .add_attribute("swap_return_amount", swap_result.net_token_output_amount)
+        .add_attribute("protocol_fee_amount", swap_result.protocol_fee_amount)
+        .add_attribute("offer_refund", refunds[0].amount)
+        .add_attribute("ask_refund", refunds[1].amount)
+        .add_attribute("liquidity_amount", share)
+        .add_messages(msgs))
 }
 
 fn execute_remove_liquidity(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     amount: Uint128,
+    receive_asset: Option<AssetInfo>,
+    min_out: Option<Uint128>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let lp_token_addr = config.pair_info.liquidity_token;
     let lp_token_balance = query_token_balance(&deps.querier, &lp_token_addr, &info.sender)?;
-    let lp_token_supply = query_lp_token_supply(&deps.querier, &lp_token_addr)?;
 
     if amount > lp_token_balance {
         return Err(ContractError::NotEnoughBalance {
@@ -527,7 +1230,45 @@ fn execute_remove_liquidity(
         });
     }
 
+    let lp_token_burn_msg = get_burn_from_msg(&lp_token_addr, &info.sender, amount)?;
+
+    withdraw_liquidity(
+        deps,
+        env,
+        info.sender,
+        amount,
+        lp_token_burn_msg,
+        receive_asset,
+        min_out,
+    )
+}
+
+/// If `receive_asset` is set, the other side withdrawn is swapped for it against the reserves
+/// left after the withdrawal
+fn withdraw_liquidity(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    amount: Uint128,
+    lp_token_burn_msg: CosmosMsg,
+    receive_asset: Option<AssetInfo>,
+    min_out: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if let Some(receive_asset) = &receive_asset {
+        if !config.pair_info.asset_infos.contains(receive_asset) {
+            return Err(ContractError::AssetMismatch {});
+        }
+    } else if min_out.is_some() {
+        return Err(ContractError::MinOutWithoutReceiveAsset {});
+    }
+
+    let lp_token_supply = query_lp_token_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let curve = get_curve(deps.storage, &config, &env)?;
+
     let liquidity = LIQUIDITY.load(deps.storage)?;
+    update_price_accumulators(deps.storage, &env, &liquidity, curve)?;
 
     let token1_amount = amount
         .checked_mul(liquidity.token_a.amount)
@@ -557,40 +1298,119 @@ fn execute_remove_liquidity(
         Ok(liquidity)
     })?;
 
-    let token1_transfer_msg = match liquidity.token_a.info {
-        AssetInfo::Token { contract_addr } => {
-            get_cw20_transfer_msg(&info.sender, &contract_addr, token1_amount)?
-        }
-        AssetInfo::NativeToken { denom } => {
-            get_bank_transfer_to_msg(&info.sender, &denom, token1_amount)
+    let withdrawn = [
+        Asset {
+            info: liquidity.token_a.info,
+            amount: token1_amount,
+        },
+        Asset {
+            info: liquidity.token_b.info,
+            amount: token2_amount,
+        },
+    ];
+
+    let receive_asset = match receive_asset {
+        Some(receive_asset) => receive_asset,
+        None => {
+            let mut msgs = vec![];
+            for asset in withdrawn {
+                msgs.push(get_asset_transfer_msg(&sender, asset)?);
+            }
+
+            return Ok(Response::new()
+                .add_messages(msgs)
+                .add_message(lp_token_burn_msg)
+                .add_attribute("liquidity_burned", amount)
+                .add_attribute("token1_returned", token1_amount)
+                .add_attribute("token2_returned", token2_amount));
         }
     };
 
-    let token2_transfer_msg = match liquidity.token_b.info {
-        AssetInfo::Token { contract_addr } => {
-            get_cw20_transfer_msg(&info.sender, &contract_addr, token2_amount)?
-        }
-        AssetInfo::NativeToken { denom } => {
-            get_bank_transfer_to_msg(&info.sender, &denom, token2_amount)
-        }
+    let (received, offered) = if withdrawn[0].info == receive_asset {
+        (&withdrawn[0], &withdrawn[1])
+    } else {
+        (&withdrawn[1], &withdrawn[0])
     };
 
-    let lp_token_burn_msg = get_burn_from_msg(&lp_token_addr, &info.sender, amount)?;
+    // the unwanted side goes through the same fee accounting as a regular swap
+    let (offer_pool, ask_pool) = get_offer_ask_reserves(deps.as_ref(), &config, &offered.info)?;
+    let swap_result = compute_swap(
+        offered.amount,
+        offer_pool.amount,
+        ask_pool.amount,
+        &config.fees,
+        curve,
+    )?;
+
+    // without min_out the swap is bounded like a swap without max_spread
+    if min_out.is_none() {
+        assert_max_spread(
+            None,
+            parse_max_spread(None)?,
+            offered.amount,
+            swap_result.net_token_output_amount,
+            swap_result.spread_amount,
+        )?;
+    }
+
+    apply_swap(deps.storage, &env, &offered.info, curve, &swap_result)?;
+
+    let return_amount = received
+        .amount
+        .checked_add(swap_result.net_token_output_amount)
+        .map_err(StdError::overflow)?;
+    if let Some(min_out) = min_out {
+        if return_amount < min_out {
+            return Err(ContractError::MinOutputAssertion {
+                min_output_amount: min_out,
+                return_amount,
+            });
+        }
+    }
+
+    let transfer_msg = get_asset_transfer_msg(
+        &sender,
+        Asset {
+            info: receive_asset,
+            amount: return_amount,
+        },
+    )?;
 
     Ok(Response::new()
-        .add_message(token1_transfer_msg)
-        .add_message(token2_transfer_msg)
+        .add_message(transfer_msg)
         .add_message(lp_token_burn_msg)
         .add_attribute("liquidity_burned", amount)
         .add_attribute("token1_returned", token1_amount)
-        .add_attribute("token2_returned", token2_amount))
+        .add_attribute("token2_returned", token2_amount)
+        .add_attribute("swap_offer_amount", offered.amount)
+        .add_attribute("swap_return_amount", swap_result.net_token_output_amount)
+        .add_attribute("protocol_fee_amount", swap_result.protocol_fee_amount)
+        .add_attribute("input_token_fee_amount", swap_result.input_token_fee_amount)
+        .add_attribute(
+            "output_token_fee_amount",
+            swap_result.output_token_fee_amount,
+        )
+        .add_attribute("return_amount", return_amount))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::PairInfo {} => query_pair_info(deps),
+        QueryMsg::Config {} => query_config(deps),
+        QueryMsg::Amp {} => query_amp(deps, env),
+        QueryMsg::AccruedProtocolFees {} => query_accrued_protocol_fees(deps),
+        QueryMsg::ReserveDrift {} => to_binary(&ReserveDriftResponse {
+            assets: get_reserve_drift(deps, &env)?,
+        }),
         QueryMsg::Liquidity {} => query_liquidity(deps),
+        QueryMsg::Simulation { offer_asset } => query_simulation(deps, env, offer_asset),
+        QueryMsg::ReverseSimulation { ask_asset } => query_reverse_simulation(deps, env, ask_asset),
+        QueryMsg::SimulateProvideSingleSided { asset } => {
+            query_simulate_provide_single_sided(deps, env, asset)
+        }
+        QueryMsg::CumulativePrices {} => query_cumulative_prices(deps, env),
+        QueryMsg::Twap { window_seconds } => query_twap(deps, env, window_seconds),
     }
 }
 
@@ -600,9 +1420,43 @@ fn query_pair_info(deps: Deps) -> StdResult<Binary> {
         assets: config.pair_info.asset_infos,
         contract_addr: config.pair_info.contract_addr,
         liquidity_token: config.pair_info.liquidity_token,
+        pair_type: config.pair_info.pair_type,
     };
 
-    Ok(to_binary(&res)?)
+    to_binary(&res)
+}
+
+fn query_config(deps: Deps) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let percent = Decimal::from_ratio(100u64, 1u64);
+
+    to_binary(&ConfigResponse {
+        owner: config.owner,
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+        protocol_fee_recipient: config.fees.protocol_fee_recipient,
+        protocol_fee_percent: config.fees.protocol_fee_percent * percent,
+        lp_fee_percent: config.fees.lp_fee_percent * percent,
+    })
+}
+
+fn query_amp(deps: Deps, env: Env) -> StdResult<Binary> {
+    let amp_config = AMP
+        .may_load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err(ContractError::NotStablePair {}.to_string()))?;
+
+    to_binary(&AmpResponse {
+        amp: amp_config.current_amp(env.block.time.seconds()),
+        init_amp: amp_config.init_amp,
+        init_amp_time: amp_config.init_amp_time,
+        next_amp: amp_config.next_amp,
+        next_amp_time: amp_config.next_amp_time,
+    })
+}
+
+fn query_accrued_protocol_fees(deps: Deps) -> StdResult<Binary> {
+    to_binary(&AccruedProtocolFeesResponse {
+        fees: PROTOCOL_FEES.load(deps.storage)?,
+    })
 }
 
 fn query_liquidity(deps: Deps) -> StdResult<Binary> {
@@ -611,5 +1465,144 @@ fn query_liquidity(deps: Deps) -> StdResult<Binary> {
         liquidity: [liquidity.token_a, liquidity.token_b],
     };
 
-    Ok(to_binary(&res)?)
+    to_binary(&res)
+}
+
+fn query_simulation(deps: Deps, env: Env, offer_asset: Asset) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let (offer_pool, ask_pool) = get_offer_ask_reserves(deps, &config, &offer_asset.info)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let res = compute_swap(
+        offer_asset.amount,
+        offer_pool.amount,
+        ask_pool.amount,
+        &config.fees,
+        get_curve(deps.storage, &config, &env)?,
+    )?;
+
+    to_binary(&SimulationResponse {
+        return_amount: res.net_token_output_amount,
+        spread_amount: res.spread_amount,
+        protocol_fee_amount: res.protocol_fee_amount,
+        input_token_fee_amount: res.input_token_fee_amount,
+        output_token_fee_amount: res.output_token_fee_amount,
+    })
+}
+
+fn query_simulate_provide_single_sided(deps: Deps, env: Env, asset: Asset) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let provide = compute_provide_single_sided(deps, &env, &config, &asset)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let mut refund_assets = [
+        Asset {
+            info: provide.offer_info,
+            amount: provide.deposits[0] - provide.used[0],
+        },
+        Asset {
+            info: provide.ask_info,
+            amount: provide.deposits[1] - provide.used[1],
+        },
+    ];
+    if refund_assets[0].info != config.pair_info.asset_infos[0] {
+        refund_assets.swap(0, 1);
+    }
+
+    to_binary(&SimulateProvideSingleSidedResponse {
+        lp_amount: provide.share,
+        swap_amount: provide.swap_amount,
+        return_amount: provide.swap_result.net_token_output_amount,
+        protocol_fee_amount: provide.swap_result.protocol_fee_amount,
+        refund_assets,
+    })
+}
+
+fn query_reverse_simulation(deps: Deps, env: Env, ask_asset: Asset) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let offer_info = if ask_asset.info.eq(&config.pair_info.asset_infos[0]) {
+        config.pair_info.asset_infos[1].clone()
+    } else if ask_asset.info.eq(&config.pair_info.asset_infos[1]) {
+        config.pair_info.asset_infos[0].clone()
+    } else {
+        return Err(StdError::generic_err(
+            ContractError::AssetMismatch {}.to_string(),
+        ));
+    };
+
+    let (offer_pool, ask_pool) = get_offer_ask_reserves(deps, &config, &offer_info)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let curve = get_curve(deps.storage, &config, &env)?;
+    let offer_amount = compute_offer_amount(
+        ask_asset.amount,
+        offer_pool.amount,
+        ask_pool.amount,
+        &config.fees,
+        curve,
+    )?;
+
+    let res = compute_swap(
+        offer_amount,
+        offer_pool.amount,
+        ask_pool.amount,
+        &config.fees,
+        curve,
+    )?;
+
+    to_binary(&ReverseSimulationResponse {
+        offer_amount,
+        return_amount: res.net_token_output_amount,
+        spread_amount: res.spread_amount,
+        protocol_fee_amount: res.protocol_fee_amount,
+        input_token_fee_amount: res.input_token_fee_amount,
+        output_token_fee_amount: res.output_token_fee_amount,
+    })
+}
+
+fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let liquidity = LIQUIDITY.load(deps.storage)?;
+    let price_cumulative = PRICE_CUMULATIVE.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let curve = get_curve(deps.storage, &config, &env)?;
+
+    let (price0_cumulative_last, price1_cumulative_last) =
+        get_cumulative_prices(&price_cumulative, &liquidity, curve, now);
+
+    to_binary(&CumulativePricesResponse {
+        assets: [liquidity.token_a, liquidity.token_b],
+        price0_cumulative_last,
+        price1_cumulative_last,
+        block_time_last: now,
+    })
+}
+
+fn query_twap(deps: Deps, env: Env, window_seconds: u64) -> StdResult<Binary> {
+    if window_seconds == 0 {
+        return Err(StdError::generic_err("window_seconds must be positive"));
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let liquidity = LIQUIDITY.load(deps.storage)?;
+    let price_cumulative = PRICE_CUMULATIVE.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let curve = get_curve(deps.storage, &config, &env)?;
+
+    let (price0_cumulative, price1_cumulative) =
+        get_cumulative_prices(&price_cumulative, &liquidity, curve, now);
+    let observation = find_observation(deps.storage, &price_cumulative, now, window_seconds)?;
+
+    let elapsed = now - observation.timestamp;
+
+    to_binary(&TwapResponse {
+        price0_average: Decimal::new(
+            price0_cumulative.wrapping_sub(observation.price0_cumulative) / Uint128::from(elapsed),
+        ),
+        price1_average: Decimal::new(
+            price1_cumulative.wrapping_sub(observation.price1_cumulative) / Uint128::from(elapsed),
+        ),
+        window_seconds: elapsed,
+    })
 }
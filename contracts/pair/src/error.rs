@@ -12,15 +12,71 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Fee must not exceed {max_percent}%")]
+    FeeTooHigh { max_percent: u64 },
+
+    #[error("Operation exceeds max spread limit")]
+    MaxSpreadAssertion {},
+
+    #[error("Max spread must not exceed {max_percent}%")]
+    AllowedSpreadAssertion { max_percent: u64 },
+
+    #[error("Operation exceeds max slippage tolerance")]
+    MaxSlippageAssertion {},
+
+    #[error("Slippage tolerance must not exceed {max_percent}%")]
+    AllowedSlippageAssertion { max_percent: u64 },
+
+    #[error(
+        "Return amount {return_amount} is less than the minimum output amount {min_output_amount}"
+    )]
+    MinOutputAssertion {
+        min_output_amount: Uint128,
+        return_amount: Uint128,
+    },
+
+    #[error("No ownership transfer has been proposed")]
+    NoOwnershipProposal {},
+
     #[error("Not enough liquidity")]
     NotEnoughLiquidity {},
 
     #[error("Asset Mismatch")]
     AssetMismatch {},
 
+    #[error("CW20 tokens must be offered through Cw20ExecuteMsg::Send")]
+    Cw20DirectSwap {},
+
+    #[error("Native tokens cannot be provided through the CW20 hook")]
+    NativeTokenInHook {},
+
+    #[error("Min lp out assertion: {lp_amount} lp tokens minted, {min_lp_out} expected")]
+    MinLpOutAssertion {
+        min_lp_out: Uint128,
+        lp_amount: Uint128,
+    },
+
+    #[error("min_out can only be set together with receive_asset")]
+    MinOutWithoutReceiveAsset {},
+
+    #[error("Only available for stable pairs")]
+    NotStablePair {},
+
+    #[error("Amplification must be set between 1 and {max_amp}")]
+    InvalidAmp { max_amp: u64 },
+
+    #[error("Amplification can change by at most {max_change} times at once")]
+    MaxAmpChangeAssertion { max_change: u64 },
+
+    #[error("Amplification can change at most once every {min_seconds} seconds")]
+    MinAmpChangingTimeAssertion { min_seconds: u64 },
+
     #[error("InvalidZeroAmount")]
     InvalidZeroAmount {},
 
+    #[error("First deposit must mint more than {min_lp} lp tokens, {given} given")]
+    MinimumLiquidityAmount { min_lp: Uint128, given: Uint128 },
+
     #[error("Not enough token amount: {need} required, {supplied} supplied")]
     NotEnoughTokenAmount { need: Uint128, supplied: Uint128 },
 
@@ -1,5 +1,7 @@
 pub mod contract;
 pub mod error;
 pub mod math;
+pub mod oracle;
+pub mod stableswap;
 pub mod state;
 pub mod utils;
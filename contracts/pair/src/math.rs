@@ -1,6 +1,32 @@
-use crate::state::FEE_SCALE_FACTOR;
+use crate::error::ContractError;
+use crate::stableswap;
+use crate::state::{Fees, FEE_SCALE_FACTOR};
 use crate::utils::fee_decimal_to_uint128;
-use cosmwasm_std::{Decimal, StdError, StdResult, Uint128, Uint256};
+use cosmwasm_std::{Decimal, Isqrt, StdError, StdResult, Uint128, Uint256};
+
+/// Invariant the pool prices swaps with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    ConstantProduct,
+    /// stableswap invariant with the amplification at the current block
+    Stable {
+        amp: u64,
+    },
+}
+
+/// Every amount involved in a single swap through the pool
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwapResult {
+    pub protocol_fee_amount: Uint128,
+    pub input_token_fee_amount: Uint128,
+    pub output_token_fee_amount: Uint128,
+    /// amount of offer token into the pool without lp fee
+    pub net_input_amount: Uint128,
+    /// amount of ask token out of the pool
+    pub net_token_output_amount: Uint128,
+    /// difference between the spot price return and the actual return
+    pub spread_amount: Uint128,
+}
 
 pub fn get_swap_output_amount(
     input_amount: Uint128,
@@ -56,26 +82,280 @@ pub fn get_lp_fee_amount(
     Ok((input_token_fee_amount, output_token_fee_amount))
 }
 
+pub fn compute_swap(
+    offer_amount: Uint128,
+    offer_reserve: Uint128,
+    ask_reserve: Uint128,
+    fees: &Fees,
+    curve: Curve,
+) -> StdResult<SwapResult> {
+    let protocol_fee_amount = get_protocol_fee_amount(offer_amount, fees.protocol_fee_percent)?;
+    let offer_amount_after_protocol_fee = offer_amount.checked_sub(protocol_fee_amount)?;
+
+    let token_bought_amount = match curve {
+        Curve::ConstantProduct => get_swap_output_amount(
+            offer_amount_after_protocol_fee,
+            offer_reserve,
+            ask_reserve,
+            fees.lp_fee_percent,
+        )?,
+        Curve::Stable { amp } => {
+            let (input_token_fee_amount, _) = get_lp_fee_amount(
+                offer_amount_after_protocol_fee,
+                Uint128::zero(),
+                fees.lp_fee_percent,
+            )?;
+            stableswap::get_swap_output_amount(
+                amp,
+                offer_amount_after_protocol_fee - input_token_fee_amount,
+                offer_reserve,
+                ask_reserve,
+            )?
+        }
+    };
+
+    let (input_token_fee_amount, output_token_fee_amount) = get_lp_fee_amount(
+        offer_amount_after_protocol_fee,
+        token_bought_amount,
+        fees.lp_fee_percent,
+    )?;
+
+    let net_input_amount = offer_amount_after_protocol_fee.checked_sub(input_token_fee_amount)?;
+    let net_token_output_amount = token_bought_amount.checked_sub(output_token_fee_amount)?;
+
+    let spot_return_amount = match curve {
+        Curve::ConstantProduct => net_input_amount.multiply_ratio(ask_reserve, offer_reserve),
+        Curve::Stable { amp } => {
+            stableswap::get_spot_return_amount(amp, net_input_amount, offer_reserve, ask_reserve)?
+        }
+    };
+    let spread_amount = spot_return_amount.saturating_sub(token_bought_amount);
+
+    Ok(SwapResult {
+        protocol_fee_amount,
+        input_token_fee_amount,
+        output_token_fee_amount,
+        net_input_amount,
+        net_token_output_amount,
+        spread_amount,
+    })
+}
+
+/// Part of a single sided deposit of `amount` swapped first, so that the rest of it and the
+/// swap return match the pool ratio after the swap
+pub fn compute_single_sided_swap_amount(
+    amount: Uint128,
+    offer_reserve: Uint128,
+    ask_reserve: Uint128,
+    fees: &Fees,
+    curve: Curve,
+) -> StdResult<Uint128> {
+    // the rest of the offer is in excess of the return as long as too little is swapped
+    let offer_in_excess = |swap_amount: Uint128| -> StdResult<bool> {
+        let res = compute_swap(swap_amount, offer_reserve, ask_reserve, fees, curve)?;
+        let offer_reserve_after_swap =
+            offer_reserve + res.net_input_amount + res.input_token_fee_amount;
+        let ask_reserve_after_swap = ask_reserve - res.net_token_output_amount;
+
+        Ok((amount - swap_amount).full_mul(ask_reserve_after_swap)
+            >= res
+                .net_token_output_amount
+                .full_mul(offer_reserve_after_swap))
+    };
+
+    // the largest swap amount which leaves the offer side in excess
+    let (mut low, mut high) = (Uint128::zero(), amount);
+    while low < high {
+        let mid = low + (high - low + Uint128::one()) / Uint128::new(2);
+        if offer_in_excess(mid)? {
+            low = mid;
+        } else {
+            high = mid - Uint128::one();
+        }
+    }
+
+    Ok(low)
+}
+
+/// LP tokens minted for `deposits` and the part of each deposit that is actually used,
+/// the rest of the side in excess is refunded
+pub fn compute_provide(
+    deposits: [Uint128; 2],
+    reserves: [Uint128; 2],
+    lp_token_supply: Uint128,
+) -> StdResult<(Uint128, [Uint128; 2])> {
+    if lp_token_supply.is_zero() {
+        // geometric mean of the first deposit, independent of the asset order and the price
+        let share = Uint128::try_from(deposits[0].full_mul(deposits[1]).isqrt())?;
+        return Ok((share, deposits));
+    }
+
+    if reserves[0].is_zero() || reserves[1].is_zero() {
+        return Err(StdError::generic_err("No liquidity"));
+    }
+
+    let amount1_optimal = deposits[0].multiply_ratio(reserves[1], reserves[0]);
+    let used = if amount1_optimal <= deposits[1] {
+        [deposits[0], amount1_optimal]
+    } else {
+        [
+            deposits[1].multiply_ratio(reserves[0], reserves[1]),
+            deposits[1],
+        ]
+    };
+
+    let share = std::cmp::min(
+        used[0].multiply_ratio(lp_token_supply, reserves[0]),
+        used[1].multiply_ratio(lp_token_supply, reserves[1]),
+    );
+
+    Ok((share, used))
+}
+
+/// Fails if the deposit ratio is further than `slippage_tolerance` from the pool ratio
+pub fn assert_slippage_tolerance(
+    slippage_tolerance: Decimal,
+    deposits: [Uint128; 2],
+    reserves: [Uint128; 2],
+) -> Result<(), ContractError> {
+    let one_minus_slippage_tolerance = Decimal::one() - slippage_tolerance;
+
+    if Decimal::from_ratio(deposits[0], deposits[1]) * one_minus_slippage_tolerance
+        > Decimal::from_ratio(reserves[0], reserves[1])
+        || Decimal::from_ratio(deposits[1], deposits[0]) * one_minus_slippage_tolerance
+            > Decimal::from_ratio(reserves[1], reserves[0])
+    {
+        return Err(ContractError::MaxSlippageAssertion {});
+    }
+
+    Ok(())
+}
+
+/// Fails if the return is further than `max_spread` from the return expected at `belief_price`,
+/// or from the spot price return if `belief_price` is not set
+pub fn assert_max_spread(
+    belief_price: Option<Decimal>,
+    max_spread: Decimal,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+) -> Result<(), ContractError> {
+    if let Some(belief_price) = belief_price {
+        if belief_price.is_zero() {
+            return Err(StdError::generic_err("belief_price must be positive").into());
+        }
+
+        let expected_return = offer_amount
+            .checked_multiply_ratio(Decimal::one().atomics(), belief_price.atomics())
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+        let spread_amount = expected_return.saturating_sub(return_amount);
+
+        if !spread_amount.is_zero()
+            && Decimal::from_ratio(spread_amount, expected_return) > max_spread
+        {
+            return Err(ContractError::MaxSpreadAssertion {});
+        }
+    } else if !spread_amount.is_zero()
+        && Decimal::from_ratio(spread_amount, return_amount + spread_amount) > max_spread
+    {
+        return Err(ContractError::MaxSpreadAssertion {});
+    }
+
+    Ok(())
+}
+
+/// Inverse of [`compute_swap`], returns the offer amount needed to receive at least `ask_amount`
+pub fn compute_offer_amount(
+    ask_amount: Uint128,
+    offer_reserve: Uint128,
+    ask_reserve: Uint128,
+    fees: &Fees,
+    curve: Curve,
+) -> StdResult<Uint128> {
+    if offer_reserve.is_zero() || ask_reserve.is_zero() {
+        return Err(StdError::generic_err("No liquidity"));
+    }
+
+    let lp_fee = fee_decimal_to_uint128(fees.lp_fee_percent)?;
+    let protocol_fee = fee_decimal_to_uint128(fees.protocol_fee_percent)?;
+
+    // amount leaving the curve before the output lp fee is taken
+    let token_bought_amount =
+        ceil_multiply_ratio(ask_amount, FEE_SCALE_FACTOR, FEE_SCALE_FACTOR - lp_fee)?;
+    if token_bought_amount >= ask_reserve {
+        return Err(StdError::generic_err("Not enough liquidity"));
+    }
+
+    let offer_reserve_after_swap = match curve {
+        Curve::ConstantProduct => {
+            let k = offer_reserve.full_mul(ask_reserve);
+            let ask_reserve_after_swap = Uint256::from(ask_reserve - token_bought_amount);
+            (k / (ask_reserve_after_swap + Uint256::one()) + Uint256::one()).try_into()?
+        }
+        Curve::Stable { amp } => stableswap::get_offer_reserve_after_swap(
+            amp,
+            token_bought_amount + Uint128::one(),
+            offer_reserve,
+            ask_reserve,
+        )?,
+    };
+    let net_input_amount = offer_reserve_after_swap.saturating_sub(offer_reserve);
+
+    // amount offered to the curve before the input lp fee is taken
+    let offer_amount_after_protocol_fee = ceil_multiply_ratio(
+        net_input_amount,
+        FEE_SCALE_FACTOR,
+        FEE_SCALE_FACTOR - lp_fee,
+    )?;
+
+    ceil_multiply_ratio(
+        offer_amount_after_protocol_fee,
+        FEE_SCALE_FACTOR,
+        FEE_SCALE_FACTOR - protocol_fee,
+    )
+}
+
+fn ceil_multiply_ratio(
+    amount: Uint128,
+    numerator: Uint128,
+    denominator: Uint128,
+) -> StdResult<Uint128> {
+    if denominator.is_zero() {
+        return Err(StdError::generic_err("fee must be less than 100%"));
+    }
+
+    let numerator = amount.full_mul(numerator);
+    let denominator = Uint256::from(denominator);
+    let result = (numerator + denominator - Uint256::one()) / denominator;
+
+    Ok(result.try_into()?)
+}
+
 #[cfg(test)]
 mod test_input_price {
-    use crate::math::get_swap_output_amount;
-    use cosmwasm_std::{Decimal, Uint128};
+    use crate::error::ContractError;
+    use crate::math::{
+        assert_max_spread, assert_slippage_tolerance, compute_offer_amount, compute_provide,
+        compute_single_sided_swap_amount, compute_swap, get_swap_output_amount, Curve,
+    };
+    use crate::state::Fees;
+    use cosmwasm_std::{Addr, Decimal, Uint128};
     use std::str::FromStr;
 
     const EXP: u128 = 1000000;
 
+    /// fees are configured in percent and stored as ratios, like the pair does on instantiate
+    fn percent(fee: &str) -> Decimal {
+        Decimal::from_str(fee).unwrap() / Decimal::from_ratio(100u64, 1u64)
+    }
+
     #[test]
     fn test_swap_1() {
         let pool_x_reserve = Uint128::new(100 * EXP);
         let pool_y_reserve = Uint128::new(3000 * EXP);
         let input_x = Uint128::new(10 * EXP);
-        let res = get_swap_output_amount(
-            input_x,
-            pool_x_reserve,
-            pool_y_reserve,
-            Decimal::from_str("0.3").unwrap(),
-        )
-        .unwrap();
+        let res = get_swap_output_amount(input_x, pool_x_reserve, pool_y_reserve, percent("0.3"))
+            .unwrap();
         assert_eq!(res.u128(), 271983269);
     }
 
@@ -84,13 +364,8 @@ mod test_input_price {
         let pool_x_reserve = Uint128::new(100 * EXP);
         let pool_y_reserve = Uint128::new(4000 * EXP);
         let input_x = Uint128::new(20 * EXP);
-        let res = get_swap_output_amount(
-            input_x,
-            pool_x_reserve,
-            pool_y_reserve,
-            Decimal::from_str("0.3").unwrap(),
-        )
-        .unwrap();
+        let res = get_swap_output_amount(input_x, pool_x_reserve, pool_y_reserve, percent("0.3"))
+            .unwrap();
         assert_eq!(res.u128(), 664999167);
     }
 
@@ -99,13 +374,298 @@ mod test_input_price {
         let pool_x_reserve = Uint128::new(100 * EXP);
         let pool_y_reserve = Uint128::new(5000 * EXP);
         let input_x = Uint128::new(40 * EXP);
-        let res = get_swap_output_amount(
+        let res = get_swap_output_amount(input_x, pool_x_reserve, pool_y_reserve, percent("0.3"))
+            .unwrap();
+        assert_eq!(res.u128(), 1425507578);
+    }
+
+    #[test]
+    fn test_fee_units() {
+        // "0.3" percent is a fee of 30 in 10_000, not 30%
+        let pool_reserve = Uint128::new(1000 * EXP);
+        let input_x = Uint128::new(10 * EXP);
+        let with_fee =
+            get_swap_output_amount(input_x, pool_reserve, pool_reserve, percent("0.3")).unwrap();
+        let without_fee =
+            get_swap_output_amount(input_x, pool_reserve, pool_reserve, Decimal::zero()).unwrap();
+        assert_eq!(
+            with_fee,
+            get_swap_output_amount(
+                input_x * Decimal::permille(997),
+                pool_reserve,
+                pool_reserve,
+                Decimal::zero()
+            )
+            .unwrap()
+        );
+        assert!(without_fee - with_fee < Uint128::new(30_000));
+
+        // the default config of 0.15% protocol fee and 0.15% lp fee
+        let fees = Fees {
+            protocol_fee_recipient: Addr::unchecked(""),
+            protocol_fee_percent: percent("0.15"),
+            lp_fee_percent: percent("0.15"),
+        };
+        let res = compute_swap(
             input_x,
+            pool_reserve,
+            pool_reserve,
+            &fees,
+            Curve::ConstantProduct,
+        )
+        .unwrap();
+        assert_eq!(res.protocol_fee_amount, Uint128::new(15_000));
+        assert_eq!(res.input_token_fee_amount, Uint128::new(14_977));
+        assert_eq!(res.net_input_amount, Uint128::new(9_970_023));
+    }
+
+    #[test]
+    fn test_provide() {
+        let reserves = [Uint128::new(100 * EXP), Uint128::new(3000 * EXP)];
+        let supply = Uint128::new(500 * EXP);
+
+        // the first deposit mints the geometric mean
+        let (share, used) = compute_provide(
+            [Uint128::new(100), Uint128::new(400)],
+            reserves,
+            Uint128::zero(),
+        )
+        .unwrap();
+        assert_eq!(share, Uint128::new(200));
+        assert_eq!(used, [Uint128::new(100), Uint128::new(400)]);
+
+        // token y in excess
+        let (share, used) = compute_provide(
+            [Uint128::new(EXP), Uint128::new(40 * EXP)],
+            reserves,
+            supply,
+        )
+        .unwrap();
+        assert_eq!(share, Uint128::new(5 * EXP));
+        assert_eq!(used, [Uint128::new(EXP), Uint128::new(30 * EXP)]);
+
+        // token x in excess
+        let (share, used) = compute_provide(
+            [Uint128::new(2 * EXP), Uint128::new(30 * EXP)],
+            reserves,
+            supply,
+        )
+        .unwrap();
+        assert_eq!(share, Uint128::new(5 * EXP));
+        assert_eq!(used, [Uint128::new(EXP), Uint128::new(30 * EXP)]);
+    }
+
+    #[test]
+    fn test_single_sided_swap_amount() {
+        let fees = Fees {
+            protocol_fee_recipient: Addr::unchecked(""),
+            protocol_fee_percent: percent("0.15"),
+            lp_fee_percent: percent("0.15"),
+        };
+        let offer_reserve = Uint128::new(100 * EXP);
+        let ask_reserve = Uint128::new(3000 * EXP);
+        let amount = Uint128::new(10 * EXP);
+
+        let swap_amount = compute_single_sided_swap_amount(
+            amount,
+            offer_reserve,
+            ask_reserve,
+            &fees,
+            Curve::ConstantProduct,
+        )
+        .unwrap();
+        // a bit less than half is swapped, the pool price moves against the swap
+        assert!(swap_amount < amount / Uint128::new(2));
+        assert!(swap_amount > amount * Decimal::percent(45));
+
+        let res = compute_swap(
+            swap_amount,
+            offer_reserve,
+            ask_reserve,
+            &fees,
+            Curve::ConstantProduct,
+        )
+        .unwrap();
+        let reserves = [
+            offer_reserve + res.net_input_amount + res.input_token_fee_amount,
+            ask_reserve - res.net_token_output_amount,
+        ];
+        let (_, used) = compute_provide(
+            [amount - swap_amount, res.net_token_output_amount],
+            reserves,
+            Uint128::new(500 * EXP),
+        )
+        .unwrap();
+
+        // only dust is left of either side
+        assert!(amount - swap_amount - used[0] <= Uint128::new(1));
+        assert!(res.net_token_output_amount - used[1] <= Uint128::new(30));
+    }
+
+    #[test]
+    fn test_slippage_tolerance() {
+        let reserves = [Uint128::new(100 * EXP), Uint128::new(3000 * EXP)];
+        let tolerance = Decimal::percent(1);
+
+        assert_slippage_tolerance(
+            tolerance,
+            [Uint128::new(EXP), Uint128::new(30 * EXP)],
+            reserves,
+        )
+        .unwrap();
+        assert_slippage_tolerance(
+            tolerance,
+            [Uint128::new(EXP), Uint128::new(303 * EXP / 10)],
+            reserves,
+        )
+        .unwrap();
+        assert_eq!(
+            assert_slippage_tolerance(
+                tolerance,
+                [Uint128::new(EXP), Uint128::new(31 * EXP)],
+                reserves
+            ),
+            Err(ContractError::MaxSlippageAssertion {})
+        );
+        assert_eq!(
+            assert_slippage_tolerance(
+                tolerance,
+                [Uint128::new(EXP), Uint128::new(29 * EXP)],
+                reserves
+            ),
+            Err(ContractError::MaxSlippageAssertion {})
+        );
+    }
+
+    #[test]
+    fn test_max_spread() {
+        let max_spread = Decimal::percent(1);
+        let offer = Uint128::new(10000);
+
+        // spread against the spot price
+        assert_max_spread(
+            None,
+            max_spread,
+            offer,
+            Uint128::new(9900),
+            Uint128::new(100),
+        )
+        .unwrap();
+        assert_eq!(
+            assert_max_spread(
+                None,
+                max_spread,
+                offer,
+                Uint128::new(9899),
+                Uint128::new(101)
+            ),
+            Err(ContractError::MaxSpreadAssertion {})
+        );
+
+        // spread against the belief price, 2 offer per ask expects 5000
+        let belief_price = Some(Decimal::from_str("2").unwrap());
+        assert_max_spread(
+            belief_price,
+            max_spread,
+            offer,
+            Uint128::new(4950),
+            Uint128::zero(),
+        )
+        .unwrap();
+        assert_eq!(
+            assert_max_spread(
+                belief_price,
+                max_spread,
+                offer,
+                Uint128::new(4949),
+                Uint128::zero()
+            ),
+            Err(ContractError::MaxSpreadAssertion {})
+        );
+        // better than expected
+        assert_max_spread(
+            belief_price,
+            max_spread,
+            offer,
+            Uint128::new(6000),
+            Uint128::zero(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_reverse_swap() {
+        let fees = Fees {
+            protocol_fee_recipient: Addr::unchecked(""),
+            protocol_fee_percent: percent("0.15"),
+            lp_fee_percent: percent("0.15"),
+        };
+        let pool_x_reserve = Uint128::new(100 * EXP);
+        let pool_y_reserve = Uint128::new(3000 * EXP);
+        let ask_y = Uint128::new(250 * EXP);
+
+        let offer_x = compute_offer_amount(
+            ask_y,
             pool_x_reserve,
             pool_y_reserve,
-            Decimal::from_str("0.3").unwrap(),
+            &fees,
+            Curve::ConstantProduct,
         )
         .unwrap();
-        assert_eq!(res.u128(), 1425507578);
+        let res = compute_swap(
+            offer_x,
+            pool_x_reserve,
+            pool_y_reserve,
+            &fees,
+            Curve::ConstantProduct,
+        )
+        .unwrap();
+        assert!(res.net_token_output_amount >= ask_y);
+
+        // rounding keeps the estimate within one unit of the minimum offer
+        let res = compute_swap(
+            offer_x - Uint128::new(2),
+            pool_x_reserve,
+            pool_y_reserve,
+            &fees,
+            Curve::ConstantProduct,
+        )
+        .unwrap();
+        assert!(res.net_token_output_amount < ask_y);
+    }
+
+    #[test]
+    fn test_stable_swap() {
+        let fees = Fees {
+            protocol_fee_recipient: Addr::unchecked(""),
+            protocol_fee_percent: percent("0.15"),
+            lp_fee_percent: percent("0.15"),
+        };
+        let curve = Curve::Stable { amp: 100 };
+        let reserve = Uint128::new(1000 * EXP);
+        let offer = Uint128::new(10 * EXP);
+
+        // close to the peg less the fees, far better than the constant product return
+        let res = compute_swap(offer, reserve, reserve, &fees, curve).unwrap();
+        let constant_product_res =
+            compute_swap(offer, reserve, reserve, &fees, Curve::ConstantProduct).unwrap();
+        assert!(res.net_token_output_amount > Uint128::new(9_940_000));
+        assert!(res.net_token_output_amount > constant_product_res.net_token_output_amount);
+        assert!(res.spread_amount < Uint128::new(10_000));
+
+        let ask = Uint128::new(10 * EXP);
+        let offer = compute_offer_amount(ask, reserve, reserve, &fees, curve).unwrap();
+        let res = compute_swap(offer, reserve, reserve, &fees, curve).unwrap();
+        assert!(res.net_token_output_amount >= ask);
+        // the solver rounds in favor of the pool, a few units above the minimum offer
+        let res = compute_swap(offer - Uint128::new(6), reserve, reserve, &fees, curve).unwrap();
+        assert!(res.net_token_output_amount < ask);
+
+        // off the peg the spread is measured against the marginal price, not against 1:1
+        let (offer_reserve, ask_reserve) = (Uint128::new(1900 * EXP), Uint128::new(100 * EXP));
+        let res =
+            compute_swap(Uint128::new(EXP), offer_reserve, ask_reserve, &fees, curve).unwrap();
+        assert!(res.net_token_output_amount < Uint128::new(EXP * 9 / 10));
+        assert!(res.spread_amount < Uint128::new(EXP / 500));
     }
 }
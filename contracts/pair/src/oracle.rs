@@ -0,0 +1,287 @@
+use crate::math::Curve;
+use crate::stableswap;
+use crate::state::{
+    Liquidity, Observation, PriceCumulative, OBSERVATIONS, OBSERVATIONS_SIZE, PRICE_CUMULATIVE,
+};
+use cosmwasm_std::{Decimal, Env, StdError, StdResult, Storage, Uint128};
+
+pub fn init_price_accumulators(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    let now = env.block.time.seconds();
+
+    OBSERVATIONS.save(
+        storage,
+        0,
+        &Observation {
+            timestamp: now,
+            price0_cumulative: Uint128::zero(),
+            price1_cumulative: Uint128::zero(),
+        },
+    )?;
+
+    PRICE_CUMULATIVE.save(
+        storage,
+        &PriceCumulative {
+            price0_cumulative_last: Uint128::zero(),
+            price1_cumulative_last: Uint128::zero(),
+            block_time_last: now,
+            observation_count: 1,
+        },
+    )
+}
+
+/// Returns the cumulative prices at `now`, accumulating the spot prices of the given reserves on
+/// `curve` since the last update. A price which does not fit in a `Decimal` is not accumulated for
+/// that period
+pub fn get_cumulative_prices(
+    price_cumulative: &PriceCumulative,
+    liquidity: &Liquidity,
+    curve: Curve,
+    now: u64,
+) -> (Uint128, Uint128) {
+    let elapsed = now.saturating_sub(price_cumulative.block_time_last);
+
+    if elapsed == 0 || liquidity.token_a.amount.is_zero() || liquidity.token_b.amount.is_zero() {
+        return (
+            price_cumulative.price0_cumulative_last,
+            price_cumulative.price1_cumulative_last,
+        );
+    }
+
+    let (reserve_a, reserve_b) = (liquidity.token_a.amount, liquidity.token_b.amount);
+    let (price0, price1) = match curve {
+        Curve::ConstantProduct => (
+            Decimal::checked_from_ratio(reserve_b, reserve_a).ok(),
+            Decimal::checked_from_ratio(reserve_a, reserve_b).ok(),
+        ),
+        Curve::Stable { amp } => {
+            // the return of one whole unit at the spot price carries the 18 decimals of a Decimal
+            let spot_price = |offer_reserve, ask_reserve| {
+                stableswap::get_spot_return_amount(
+                    amp,
+                    Decimal::one().atomics(),
+                    offer_reserve,
+                    ask_reserve,
+                )
+                .ok()
+                .map(Decimal::new)
+            };
+            (
+                spot_price(reserve_a, reserve_b),
+                spot_price(reserve_b, reserve_a),
+            )
+        }
+    };
+
+    // accumulators are allowed to overflow, only differences between them are meaningful
+    let accumulate = |cumulative_last: Uint128, price: Option<Decimal>| match price {
+        Some(price) => {
+            cumulative_last.wrapping_add(price.atomics().wrapping_mul(Uint128::from(elapsed)))
+        }
+        None => cumulative_last,
+    };
+
+    (
+        accumulate(price_cumulative.price0_cumulative_last, price0),
+        accumulate(price_cumulative.price1_cumulative_last, price1),
+    )
+}
+
+/// Accumulates prices with the reserves before they change and records an observation.
+/// It must be called before every update of `LIQUIDITY`
+pub fn update_price_accumulators(
+    storage: &mut dyn Storage,
+    env: &Env,
+    liquidity: &Liquidity,
+    curve: Curve,
+) -> StdResult<()> {
+    let mut price_cumulative = PRICE_CUMULATIVE.load(storage)?;
+    let now = env.block.time.seconds();
+
+    if now <= price_cumulative.block_time_last {
+        return Ok(());
+    }
+
+    let (price0_cumulative, price1_cumulative) =
+        get_cumulative_prices(&price_cumulative, liquidity, curve, now);
+
+    price_cumulative.price0_cumulative_last = price0_cumulative;
+    price_cumulative.price1_cumulative_last = price1_cumulative;
+    price_cumulative.block_time_last = now;
+
+    OBSERVATIONS.save(
+        storage,
+        price_cumulative.observation_count % OBSERVATIONS_SIZE,
+        &Observation {
+            timestamp: now,
+            price0_cumulative,
+            price1_cumulative,
+        },
+    )?;
+    price_cumulative.observation_count += 1;
+
+    PRICE_CUMULATIVE.save(storage, &price_cumulative)
+}
+
+/// Returns the newest observation made at least `window_seconds` before `now`
+pub fn find_observation(
+    storage: &dyn Storage,
+    price_cumulative: &PriceCumulative,
+    now: u64,
+    window_seconds: u64,
+) -> StdResult<Observation> {
+    let target = now
+        .checked_sub(window_seconds)
+        .ok_or_else(|| StdError::generic_err("Window is longer than the chain history"))?;
+
+    let stored = price_cumulative.observation_count.min(OBSERVATIONS_SIZE);
+
+    for i in 1..=stored {
+        let slot = (price_cumulative.observation_count - i) % OBSERVATIONS_SIZE;
+        let observation = OBSERVATIONS.load(storage, slot)?;
+
+        if observation.timestamp <= target {
+            return Ok(observation);
+        }
+    }
+
+    Err(StdError::generic_err(
+        "Not enough observations for the requested window",
+    ))
+}
+
+#[cfg(test)]
+mod test_oracle {
+    use crate::math::Curve;
+    use crate::oracle::{
+        find_observation, get_cumulative_prices, init_price_accumulators, update_price_accumulators,
+    };
+    use crate::state::{Liquidity, PRICE_CUMULATIVE};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::{Addr, Uint128};
+    use ysip::asset::{Asset, AssetInfo};
+
+    const PRICE_PRECISION: u128 = 10u128.pow(18);
+
+    fn liquidity(token_a: u128, token_b: u128) -> Liquidity {
+        Liquidity {
+            token_a: Asset {
+                info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("token"),
+                },
+                amount: Uint128::new(token_a),
+            },
+            token_b: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "ukrw".to_string(),
+                },
+                amount: Uint128::new(token_b),
+            },
+        }
+    }
+
+    #[test]
+    fn test_twap() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let start = env.block.time.seconds();
+
+        init_price_accumulators(&mut deps.storage, &env).unwrap();
+
+        // price 2 for the first 100 seconds, then price 4 for the next 100 seconds
+        for reserves in [liquidity(100, 200), liquidity(100, 400)] {
+            env.block.time = env.block.time.plus_seconds(100);
+            update_price_accumulators(&mut deps.storage, &env, &reserves, Curve::ConstantProduct)
+                .unwrap();
+        }
+
+        let price_cumulative = PRICE_CUMULATIVE.load(&deps.storage).unwrap();
+        assert_eq!(price_cumulative.observation_count, 3);
+
+        let now = start + 200;
+        let (price0_cumulative, _) = get_cumulative_prices(
+            &price_cumulative,
+            &liquidity(100, 400),
+            Curve::ConstantProduct,
+            now,
+        );
+
+        let observation = find_observation(&deps.storage, &price_cumulative, now, 100).unwrap();
+        assert_eq!(observation.timestamp, start + 100);
+        assert_eq!(
+            (price0_cumulative - observation.price0_cumulative) / Uint128::new(100),
+            Uint128::new(4 * PRICE_PRECISION)
+        );
+
+        let observation = find_observation(&deps.storage, &price_cumulative, now, 200).unwrap();
+        assert_eq!(observation.timestamp, start);
+        assert_eq!(
+            (price0_cumulative - observation.price0_cumulative) / Uint128::new(200),
+            Uint128::new(3 * PRICE_PRECISION)
+        );
+
+        find_observation(&deps.storage, &price_cumulative, now, 201).unwrap_err();
+    }
+
+    #[test]
+    fn test_price_overflow() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        init_price_accumulators(&mut deps.storage, &env).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        update_price_accumulators(
+            &mut deps.storage,
+            &env,
+            &liquidity(100, 200),
+            Curve::ConstantProduct,
+        )
+        .unwrap();
+
+        // price0 does not fit in a Decimal and keeps its last value instead of panicking
+        let price_cumulative = PRICE_CUMULATIVE.load(&deps.storage).unwrap();
+        let now = env.block.time.seconds() + 100;
+        let (price0_cumulative, price1_cumulative) = get_cumulative_prices(
+            &price_cumulative,
+            &liquidity(1, u128::MAX),
+            Curve::ConstantProduct,
+            now,
+        );
+
+        assert_eq!(price0_cumulative, price_cumulative.price0_cumulative_last);
+        assert_eq!(price0_cumulative, Uint128::new(200 * PRICE_PRECISION));
+        // price1 rounds down to zero
+        assert_eq!(price1_cumulative, price_cumulative.price1_cumulative_last);
+    }
+
+    #[test]
+    fn test_stable_price() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        init_price_accumulators(&mut deps.storage, &env).unwrap();
+        let price_cumulative = PRICE_CUMULATIVE.load(&deps.storage).unwrap();
+        let now = env.block.time.seconds() + 1;
+
+        // balanced stable reserves are at the peg
+        let (price0_cumulative, price1_cumulative) = get_cumulative_prices(
+            &price_cumulative,
+            &liquidity(1_000_000, 1_000_000),
+            Curve::Stable { amp: 100 },
+            now,
+        );
+        assert!(price0_cumulative.abs_diff(Uint128::new(PRICE_PRECISION)) <= Uint128::new(1));
+        assert!(price1_cumulative.abs_diff(Uint128::new(PRICE_PRECISION)) <= Uint128::new(1));
+
+        // an imbalanced stable pool is much closer to the peg than the reserve ratio
+        let (price0_cumulative, price1_cumulative) = get_cumulative_prices(
+            &price_cumulative,
+            &liquidity(1_500_000, 500_000),
+            Curve::Stable { amp: 100 },
+            now,
+        );
+        assert!(price0_cumulative < Uint128::new(PRICE_PRECISION));
+        assert!(price0_cumulative > Uint128::new(PRICE_PRECISION / 3));
+        assert!(price1_cumulative > Uint128::new(PRICE_PRECISION));
+        assert!(price1_cumulative < Uint128::new(3 * PRICE_PRECISION));
+    }
+}
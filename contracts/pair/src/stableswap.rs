@@ -0,0 +1,237 @@
+use cosmwasm_std::{StdError, StdResult, Uint128, Uint256};
+
+/// Number of assets in the pool
+const N_COINS: u8 = 2;
+/// Newton's method stops after this many iterations
+const ITERATIONS: u8 = 64;
+
+/// Amplification times n^n, as in the stableswap invariant
+/// `A * n^n * sum(x) + D = A * D * n^n + D^(n + 1) / (n^n * prod(x))`
+fn ann(amp: u64) -> Uint256 {
+    Uint256::from(amp) * Uint256::from(N_COINS * N_COINS)
+}
+
+/// Invariant D of the reserves `x` and `y`, solved with Newton's method
+pub fn compute_d(amp: u64, x: Uint128, y: Uint128) -> StdResult<Uint256> {
+    if x.is_zero() || y.is_zero() {
+        return Err(StdError::generic_err("No liquidity"));
+    }
+
+    let n = Uint256::from(N_COINS);
+    let ann = ann(amp);
+    let (x, y) = (Uint256::from(x), Uint256::from(y));
+    let sum = x.checked_add(y)?;
+
+    let mut d = sum;
+    for _ in 0..ITERATIONS {
+        let d_p = compute_d_p(d, x, y)?;
+        let d_prev = d;
+        let numerator = ann
+            .checked_mul(sum)?
+            .checked_add(d_p.checked_mul(n)?)?
+            .checked_mul(d)?;
+        let denominator = ann
+            .checked_sub(Uint256::one())?
+            .checked_mul(d)?
+            .checked_add((n + Uint256::one()).checked_mul(d_p)?)?;
+        d = numerator.checked_div(denominator)?;
+
+        if abs_diff(d, d_prev) <= Uint256::one() {
+            return Ok(d);
+        }
+    }
+
+    Err(StdError::generic_err("Invariant did not converge"))
+}
+
+/// Reserve of the other asset which keeps the invariant `d` when one reserve is `x`,
+/// solved with Newton's method
+pub fn compute_y(amp: u64, x: Uint128, d: Uint256) -> StdResult<Uint128> {
+    if x.is_zero() {
+        return Err(StdError::generic_err("No liquidity"));
+    }
+
+    let n = Uint256::from(N_COINS);
+    let ann = ann(amp);
+    let x = Uint256::from(x);
+
+    let c = d
+        .checked_mul(d)?
+        .checked_div(x.checked_mul(n)?)?
+        .checked_mul(d)?
+        .checked_div(ann.checked_mul(n)?)?;
+    let b = x.checked_add(d.checked_div(ann)?)?;
+
+    let mut y = d;
+    for _ in 0..ITERATIONS {
+        let y_prev = y;
+        y = y
+            .checked_mul(y)?
+            .checked_add(c)?
+            .checked_div(y.checked_mul(n)?.checked_add(b)?.checked_sub(d)?)?;
+
+        if abs_diff(y, y_prev) <= Uint256::one() {
+            return Ok(y.try_into()?);
+        }
+    }
+
+    Err(StdError::generic_err("Reserve did not converge"))
+}
+
+/// Ask amount taken out of the pool when `offer_amount` is added to the offer reserve
+pub fn get_swap_output_amount(
+    amp: u64,
+    offer_amount: Uint128,
+    offer_reserve: Uint128,
+    ask_reserve: Uint128,
+) -> StdResult<Uint128> {
+    let d = compute_d(amp, offer_reserve, ask_reserve)?;
+    let ask_reserve_after_swap = compute_y(amp, offer_reserve.checked_add(offer_amount)?, d)?;
+
+    // rounded down in favor of the pool
+    Ok(ask_reserve
+        .saturating_sub(ask_reserve_after_swap)
+        .saturating_sub(Uint128::one()))
+}
+
+/// Offer reserve needed to take `ask_amount` out of the pool, rounded up in favor of the pool
+pub fn get_offer_reserve_after_swap(
+    amp: u64,
+    ask_amount: Uint128,
+    offer_reserve: Uint128,
+    ask_reserve: Uint128,
+) -> StdResult<Uint128> {
+    let d = compute_d(amp, offer_reserve, ask_reserve)?;
+    let offer_reserve_after_swap = compute_y(amp, ask_reserve.checked_sub(ask_amount)?, d)?;
+
+    Ok(offer_reserve_after_swap + Uint128::one())
+}
+
+/// Ask amount `offer_amount` is worth at the marginal price of the current reserves, the spot
+/// price is `-dy/dx` of the invariant at the reserves `x` (offer) and `y` (ask)
+pub fn get_spot_return_amount(
+    amp: u64,
+    offer_amount: Uint128,
+    offer_reserve: Uint128,
+    ask_reserve: Uint128,
+) -> StdResult<Uint128> {
+    let ann = ann(amp);
+    let d = compute_d(amp, offer_reserve, ask_reserve)?;
+    let (x, y) = (Uint256::from(offer_reserve), Uint256::from(ask_reserve));
+
+    // with d_p = D^(n + 1) / (n^n * x * y) the partial derivatives of the invariant are
+    // `Ann + d_p / x` and `Ann + d_p / y`, both sides are multiplied by `x` here
+    let d_p = compute_d_p(d, x, y)?;
+    let numerator = ann.checked_mul(x)?.checked_add(d_p)?;
+    let denominator = ann.checked_mul(x)?.checked_add(
+        d_p.checked_multiply_ratio(x, y)
+            .map_err(|e| StdError::generic_err(e.to_string()))?,
+    )?;
+
+    Ok(Uint256::from(offer_amount)
+        .checked_multiply_ratio(numerator, denominator)
+        .map_err(|e| StdError::generic_err(e.to_string()))?
+        .try_into()?)
+}
+
+/// `D^(n + 1) / (n^n * x * y)`, divided by one reserve at a time to stay in range
+fn compute_d_p(d: Uint256, x: Uint256, y: Uint256) -> StdResult<Uint256> {
+    let n = Uint256::from(N_COINS);
+
+    Ok(d.checked_mul(d)?
+        .checked_div(x.checked_mul(n)?)?
+        .checked_mul(d)?
+        .checked_div(y.checked_mul(n)?)?)
+}
+
+fn abs_diff(a: Uint256, b: Uint256) -> Uint256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+#[cfg(test)]
+mod test_stableswap {
+    use crate::stableswap::{compute_d, compute_y, get_spot_return_amount, get_swap_output_amount};
+    use cosmwasm_std::{Uint128, Uint256};
+
+    const EXP: u128 = 1000000;
+
+    #[test]
+    fn test_invariant() {
+        // balanced reserves sum up to the invariant
+        let d = compute_d(100, Uint128::new(1000 * EXP), Uint128::new(1000 * EXP)).unwrap();
+        assert_eq!(d, Uint256::from(2000 * EXP));
+
+        // the invariant is kept for the reserve it is solved for
+        let d = compute_d(100, Uint128::new(1200 * EXP), Uint128::new(800 * EXP)).unwrap();
+        let y = compute_y(100, Uint128::new(1200 * EXP), d).unwrap();
+        assert!(y.abs_diff(Uint128::new(800 * EXP)) <= Uint128::new(1));
+    }
+
+    #[test]
+    fn test_swap() {
+        let reserve = Uint128::new(1000 * EXP);
+        let offer_amount = Uint128::new(10 * EXP);
+
+        // close to the peg with a high amplification
+        let stable_return = get_swap_output_amount(100, offer_amount, reserve, reserve).unwrap();
+        assert!(stable_return > Uint128::new(9_990_000));
+        assert!(stable_return < offer_amount);
+
+        // the constant product return 9_900_990 with a low amplification
+        let flat_return = get_swap_output_amount(1, offer_amount, reserve, reserve).unwrap();
+        assert!(flat_return < stable_return);
+        assert!(flat_return > Uint128::new(9_900_990));
+
+        // the price moves against the offer once the pool is imbalanced
+        let imbalanced_return = get_swap_output_amount(
+            100,
+            offer_amount,
+            Uint128::new(1900 * EXP),
+            Uint128::new(100 * EXP),
+        )
+        .unwrap();
+        assert!(imbalanced_return < stable_return);
+    }
+
+    #[test]
+    fn test_spot_price() {
+        let amount = Uint128::new(EXP);
+
+        // balanced reserves are at the peg for any amplification
+        for amp in [1, 100, 1_000_000] {
+            let reserve = Uint128::new(1000 * EXP);
+            let spot_return = get_spot_return_amount(amp, amount, reserve, reserve).unwrap();
+            assert!(spot_return.abs_diff(amount) <= Uint128::new(1));
+        }
+
+        // the marginal price of an imbalanced pool is off the peg, less with a higher amplification
+        let (x, y) = (Uint128::new(1900 * EXP), Uint128::new(100 * EXP));
+        let flat_return = get_spot_return_amount(1, amount, x, y).unwrap();
+        let stable_return = get_spot_return_amount(100, amount, x, y).unwrap();
+        assert!(flat_return < stable_return);
+        assert!(stable_return < amount);
+        // and the other way around the asset in short supply is worth more than the peg
+        assert!(get_spot_return_amount(100, amount, y, x).unwrap() > amount);
+
+        // small swaps return close to the spot price
+        let offer_amount = Uint128::new(1000);
+        let swap_return = get_swap_output_amount(100, offer_amount, x, y).unwrap();
+        let spot_return = get_spot_return_amount(100, offer_amount, x, y).unwrap();
+        assert!(swap_return <= spot_return);
+        assert!(spot_return - swap_return <= Uint128::new(2));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // the invariant of reserves close to u128::MAX is out of the range of Uint256
+        let (x, y) = (Uint128::MAX, Uint128::MAX);
+        let amount = Uint128::new(EXP);
+        assert!(compute_d(100, x, y).is_err());
+        assert!(get_swap_output_amount(100, amount, x, y).is_err());
+        assert!(get_spot_return_amount(100, amount, x, y).is_err());
+    }
+}
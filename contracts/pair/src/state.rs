@@ -1,5 +1,5 @@
 use cosmwasm_std::{Addr, Decimal, Uint128};
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use ysip::asset::Asset;
@@ -7,9 +7,27 @@ use ysip::pair::PairInfo;
 
 pub const FEE_SCALE_FACTOR: Uint128 = Uint128::new(10_000);
 pub const FEE_DECIMAL_PRECISION: Uint128 = Uint128::new(10u128.pow(18));
+/// lp tokens minted to the pair itself on the first deposit
+pub const MINIMUM_LIQUIDITY: Uint128 = Uint128::new(1_000);
+/// upper bound of the protocol fee in percent
+pub const MAX_PROTOCOL_FEE_PERCENT: u64 = 1;
+/// upper bound of the lp fee in percent
+pub const MAX_LP_FEE_PERCENT: u64 = 1;
+/// number of price observations kept in the ring buffer. At most one observation is written per
+/// block with a reserve change, so the longest TWAP window is bounded by the age of the oldest of
+/// the last 64 observations, which shrinks as the pair gets busier
+pub const OBSERVATIONS_SIZE: u64 = 64;
+/// upper bound of the amplification of a stable pair
+pub const MAX_AMP: u64 = 1_000_000;
+/// the amplification can be ramped to at most this multiple or fraction of its current value
+pub const MAX_AMP_CHANGE: u64 = 10;
+/// minimum duration of an amplification ramp and minimum time between two ramps, in seconds
+pub const MIN_AMP_CHANGING_TIME: u64 = 86_400;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
+    /// allowed to update the fees
+    pub owner: Addr,
     pub pair_info: PairInfo,
     pub fees: Fees,
 }
@@ -27,5 +45,91 @@ pub struct Liquidity {
     pub token_b: Asset,
 }
 
+/// Cumulative prices are scaled by 10^18 (`Decimal` atomics) and multiplied by elapsed seconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PriceCumulative {
+    /// price of token_a in token_b, accumulated over time
+    pub price0_cumulative_last: Uint128,
+    /// price of token_b in token_a, accumulated over time
+    pub price1_cumulative_last: Uint128,
+    pub block_time_last: u64,
+    /// total number of observations ever written, the next slot is `count % OBSERVATIONS_SIZE`
+    pub observation_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Observation {
+    pub timestamp: u64,
+    pub price0_cumulative: Uint128,
+    pub price1_cumulative: Uint128,
+}
+
+/// Amplification of a stable pair, ramped linearly from `init_amp` to `next_amp`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct AmpConfig {
+    pub init_amp: u64,
+    /// block time in seconds
+    pub init_amp_time: u64,
+    pub next_amp: u64,
+    /// block time in seconds
+    pub next_amp_time: u64,
+}
+
+impl AmpConfig {
+    pub fn current_amp(&self, now: u64) -> u64 {
+        if now >= self.next_amp_time {
+            return self.next_amp;
+        }
+
+        let elapsed = now.saturating_sub(self.init_amp_time) as u128;
+        let duration = (self.next_amp_time - self.init_amp_time) as u128;
+        let (init_amp, next_amp) = (self.init_amp as u128, self.next_amp as u128);
+
+        let amp = if next_amp > init_amp {
+            init_amp + (next_amp - init_amp) * elapsed / duration
+        } else {
+            init_amp - (init_amp - next_amp) * elapsed / duration
+        };
+
+        amp as u64
+    }
+}
+
 pub const CONFIG: Item<Config> = Item::new("config");
+/// proposed owner until the ownership is accepted
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
 pub const LIQUIDITY: Item<Liquidity> = Item::new("liquidity");
+/// protocol fees accrued since the last collection, in the order of `pair_info.asset_infos`
+pub const PROTOCOL_FEES: Item<[Asset; 2]> = Item::new("protocol_fees");
+pub const PRICE_CUMULATIVE: Item<PriceCumulative> = Item::new("price_cumulative");
+pub const OBSERVATIONS: Map<u64, Observation> = Map::new("observations");
+/// only saved for stable pairs
+pub const AMP: Item<AmpConfig> = Item::new("amp");
+
+#[cfg(test)]
+mod test_amp {
+    use crate::state::AmpConfig;
+
+    #[test]
+    fn test_current_amp() {
+        let ramp_up = AmpConfig {
+            init_amp: 100,
+            init_amp_time: 1_000,
+            next_amp: 200,
+            next_amp_time: 2_000,
+        };
+        assert_eq!(ramp_up.current_amp(1_000), 100);
+        assert_eq!(ramp_up.current_amp(1_500), 150);
+        assert_eq!(ramp_up.current_amp(2_000), 200);
+        assert_eq!(ramp_up.current_amp(5_000), 200);
+
+        let ramp_down = AmpConfig {
+            init_amp: 200,
+            init_amp_time: 1_000,
+            next_amp: 100,
+            next_amp_time: 2_000,
+        };
+        assert_eq!(ramp_down.current_amp(1_250), 175);
+        assert_eq!(ramp_down.current_amp(2_000), 100);
+    }
+}
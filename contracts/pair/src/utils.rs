@@ -32,6 +32,7 @@ pub fn get_provide_liquidity_msg(
                 amount: output_token_fee_amount,
             },
         ],
+        slippage_tolerance: None,
     };
 
     Ok(CosmosMsg::Wasm(WasmMsg::Execute {
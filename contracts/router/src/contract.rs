@@ -0,0 +1,494 @@
+use crate::error::ContractError;
+use crate::state::{Config, CONFIG};
+use cosmwasm_std::{
+    coins, from_binary, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use ysip::asset::{Asset, AssetInfo};
+use ysip::factory::QueryMsg as FactoryQueryMsg;
+use ysip::pair::{
+    Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg, PairInfo,
+    QueryMsg as PairQueryMsg, SimulationResponse, MAX_ALLOWED_SPREAD,
+};
+use ysip::router::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
+    SimulateSwapOperationsResponse, SwapOperation,
+};
+
+const CONTRACT_NAME: &str = "ysip-router-contract";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let config = Config {
+        factory: deps.api.addr_validate(&msg.factory)?,
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "instantiate"))
+}
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::ExecuteSwapOperations {
+            operations,
+            minimum_receive,
+            to,
+        } => {
+            let offer_amount = match operations.first() {
+                // CW20 offers have to be sent through the Receive hook
+                Some(SwapOperation {
+                    offer_asset_info: AssetInfo::Token { .. },
+                    ..
+                }) => return Err(ContractError::Cw20DirectSwap {}),
+                Some(SwapOperation {
+                    offer_asset_info: AssetInfo::NativeToken { denom },
+                    ..
+                }) => match info.funds.as_slice() {
+                    [coin] if coin.denom == *denom && !coin.amount.is_zero() => coin.amount,
+                    _ => return Err(ContractError::InvalidFunds {}),
+                },
+                None => return Err(ContractError::NoSwapOperations {}),
+            };
+
+            execute_swap_operations(
+                deps,
+                env,
+                info.sender,
+                operations,
+                offer_amount,
+                minimum_receive,
+                to,
+            )
+        }
+        ExecuteMsg::ExecuteSwapOperation {
+            operation,
+            offer_amount,
+            to,
+        } => execute_swap_operation(deps, env, info, operation, offer_amount, to),
+        ExecuteMsg::AssertMinimumReceive {
+            asset_info,
+            prev_balance,
+            minimum_receive,
+            receiver,
+        } => assert_minimum_receive(
+            deps.as_ref(),
+            env,
+            info,
+            asset_info,
+            prev_balance,
+            minimum_receive,
+            receiver,
+        ),
+    }
+}
+
+fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::ExecuteSwapOperations {
+            operations,
+            minimum_receive,
+            to,
+        } => {
+            // the sent token has to be the first offer asset
+            match operations.first() {
+                Some(SwapOperation {
+                    offer_asset_info: AssetInfo::Token { contract_addr },
+                    ..
+                }) if *contract_addr == info.sender => {}
+                _ => return Err(ContractError::InvalidSwapOperations {}),
+            }
+
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            execute_swap_operations(
+                deps,
+                env,
+                sender,
+                operations,
+                cw20_msg.amount,
+                minimum_receive,
+                to,
+            )
+        }
+    }
+}
+
+fn assert_operations(operations: &[SwapOperation]) -> Result<(), ContractError> {
+    if operations.is_empty() {
+        return Err(ContractError::NoSwapOperations {});
+    }
+
+    if operations
+        .windows(2)
+        .any(|pair| pair[0].ask_asset_info != pair[1].offer_asset_info)
+    {
+        return Err(ContractError::InvalidSwapOperations {});
+    }
+
+    Ok(())
+}
+
+/// Every operation is executed by the router itself, the first one swaps only the sent
+/// `offer_amount` and each following swap uses the output of the previous one as its offer amount
+fn execute_swap_operations(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    operations: Vec<SwapOperation>,
+    offer_amount: Uint128,
+    minimum_receive: Uint128,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_operations(&operations)?;
+
+    let receiver = match to {
+        Some(to) => deps.api.addr_validate(&to)?,
+        None => sender,
+    };
+    let target_asset_info = operations.last().unwrap().ask_asset_info.clone();
+    let operations_len = operations.len();
+
+    let mut msgs = operations
+        .into_iter()
+        .enumerate()
+        .map(|(i, operation)| {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation,
+                    offer_amount: (i == 0).then_some(offer_amount),
+                    to: (i == operations_len - 1).then(|| receiver.to_string()),
+                })?,
+                funds: vec![],
+            }))
+        })
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    let prev_balance = target_asset_info.query_pool(&deps.querier, &receiver)?;
+    msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+            asset_info: target_asset_info,
+            prev_balance,
+            minimum_receive,
+            receiver: receiver.to_string(),
+        })?,
+        funds: vec![],
+    }));
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_swap_operations")
+        .add_attribute("receiver", receiver)
+        .add_attribute("operations", operations_len.to_string())
+        .add_messages(msgs))
+}
+
+fn query_pair_info(deps: Deps, operation: &SwapOperation) -> StdResult<PairInfo> {
+    let config = CONFIG.load(deps.storage)?;
+    deps.querier.query_wasm_smart(
+        config.factory,
+        &FactoryQueryMsg::Pair {
+            asset_infos: [
+                operation.offer_asset_info.clone(),
+                operation.ask_asset_info.clone(),
+            ],
+        },
+    )
+}
+
+fn execute_swap_operation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operation: SwapOperation,
+    offer_amount: Option<Uint128>,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_info = query_pair_info(deps.as_ref(), &operation)?;
+    let offer_asset = Asset {
+        amount: match offer_amount {
+            Some(offer_amount) => offer_amount,
+            None => operation
+                .offer_asset_info
+                .query_pool(&deps.querier, &env.contract.address)?,
+        },
+        info: operation.offer_asset_info,
+    };
+
+    // the spread of the whole route is bounded by minimum_receive
+    let msg = match &offer_asset.info {
+        AssetInfo::NativeToken { denom } => WasmMsg::Execute {
+            contract_addr: pair_info.contract_addr.to_string(),
+            funds: coins(offer_asset.amount.u128(), denom),
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset: offer_asset.clone(),
+                min_output_amount: None,
+                belief_price: None,
+                max_spread: Some(MAX_ALLOWED_SPREAD.to_string()),
+                to,
+            })?,
+        },
+        AssetInfo::Token { contract_addr } => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: pair_info.contract_addr.to_string(),
+                amount: offer_asset.amount,
+                msg: to_binary(&PairCw20HookMsg::Swap {
+                    min_output_amount: None,
+                    belief_price: None,
+                    max_spread: Some(MAX_ALLOWED_SPREAD.to_string()),
+                    to,
+                })?,
+            })?,
+        },
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_swap_operation")
+        .add_attribute("pair", pair_info.contract_addr)
+        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("offer_amount", offer_asset.amount)
+        .add_message(msg))
+}
+
+fn assert_minimum_receive(
+    deps: Deps,
+    env: Env,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    prev_balance: Uint128,
+    minimum_receive: Uint128,
+    receiver: String,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let receiver = deps.api.addr_validate(&receiver)?;
+    let balance = asset_info.query_pool(&deps.querier, &receiver)?;
+    let amount = balance.saturating_sub(prev_balance);
+
+    if amount < minimum_receive {
+        return Err(ContractError::MinimumReceiveAssertion {
+            minimum_receive,
+            amount,
+        });
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "assert_minimum_receive")
+        .add_attribute("return_amount", amount))
+}
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => query_config(deps),
+        QueryMsg::SimulateSwapOperations {
+            offer_amount,
+            operations,
+        } => query_simulate_swap_operations(deps, offer_amount, operations),
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+
+    to_binary(&ConfigResponse {
+        factory: config.factory,
+    })
+}
+
+fn query_simulate_swap_operations(
+    deps: Deps,
+    offer_amount: Uint128,
+    operations: Vec<SwapOperation>,
+) -> StdResult<Binary> {
+    assert_operations(&operations).map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let mut amount = offer_amount;
+    for operation in operations {
+        let pair_info = query_pair_info(deps, &operation)?;
+        let res: SimulationResponse = deps.querier.query_wasm_smart(
+            pair_info.contract_addr,
+            &PairQueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: operation.offer_asset_info,
+                    amount,
+                },
+            },
+        )?;
+        amount = res.return_amount;
+    }
+
+    to_binary(&SimulateSwapOperationsResponse { amount })
+}
+
+#[cfg(test)]
+mod test_router {
+    use crate::contract::{execute, instantiate};
+    use crate::error::ContractError;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, coins, to_binary, Addr, CosmosMsg, Uint128, WasmMsg};
+    use ysip::asset::AssetInfo;
+    use ysip::router::{ExecuteMsg, InstantiateMsg, SwapOperation};
+
+    fn operation(offer: &str, ask: &str) -> SwapOperation {
+        SwapOperation {
+            offer_asset_info: AssetInfo::Token {
+                contract_addr: Addr::unchecked(offer),
+            },
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: Addr::unchecked(ask),
+            },
+        }
+    }
+
+    #[test]
+    fn test_execute_swap_operations() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            InstantiateMsg {
+                factory: "factory".to_string(),
+            },
+        )
+        .unwrap();
+
+        let swap = |operations: Vec<SwapOperation>| ExecuteMsg::ExecuteSwapOperations {
+            operations,
+            minimum_receive: Uint128::new(100),
+            to: None,
+        };
+        let ukrw = AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user", &[]),
+            swap(vec![]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoSwapOperations {});
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user", &[]),
+            swap(vec![operation("a", "b")]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Cw20DirectSwap {});
+
+        // exactly one non-zero coin of the first offer asset has to be sent
+        let mut first = operation("a", "b");
+        first.offer_asset_info = ukrw.clone();
+        for funds in [
+            vec![],
+            coins(100, "uusd"),
+            coins(0, "ukrw"),
+            vec![coin(100, "ukrw"), coin(100, "uusd")],
+        ] {
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("user", &funds),
+                swap(vec![first.clone(), operation("b", "c")]),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidFunds {});
+        }
+
+        // second offer asset is not the first ask asset
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user", &coins(100, "ukrw")),
+            swap(vec![first.clone(), operation("c", "d")]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidSwapOperations {});
+
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user", &coins(100, "ukrw")),
+            ExecuteMsg::ExecuteSwapOperations {
+                operations: vec![first.clone(), operation("b", "c")],
+                minimum_receive: Uint128::new(100),
+                to: None,
+            },
+        )
+        .unwrap();
+        // two swaps and the final assertion
+        assert_eq!(res.messages.len(), 3);
+
+        // only the sent amount is swapped on the first hop
+        let swap_msg = |operation, offer_amount, to| {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation,
+                    offer_amount,
+                    to,
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        };
+        assert_eq!(
+            res.messages[0].msg,
+            swap_msg(first, Some(Uint128::new(100)), None)
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            swap_msg(operation("b", "c"), None, Some("user".to_string()))
+        );
+
+        // single operations can only be executed by the router itself
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("user", &[]),
+            ExecuteMsg::ExecuteSwapOperation {
+                operation: operation("a", "b"),
+                offer_amount: None,
+                to: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+}
@@ -0,0 +1,29 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("CW20 tokens must be offered through Cw20ExecuteMsg::Send")]
+    Cw20DirectSwap {},
+
+    #[error("Exactly one non-zero coin of the first offer asset has to be sent")]
+    InvalidFunds {},
+
+    #[error("Must provide at least one swap operation")]
+    NoSwapOperations {},
+
+    #[error("Swap operations are not chained, offer asset has to be the previous ask asset")]
+    InvalidSwapOperations {},
+
+    #[error("Assertion failed; minimum receive amount: {minimum_receive}, swap amount: {amount}")]
+    MinimumReceiveAssertion {
+        minimum_receive: Uint128,
+        amount: Uint128,
+    },
+}
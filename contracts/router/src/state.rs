@@ -0,0 +1,12 @@
+use cosmwasm_std::Addr;
+use cw_storage_plus::Item;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// factory used to look up the pair of every swap operation
+    pub factory: Addr,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
@@ -7,7 +7,10 @@ use cw20::{
     AllAccountsResponse, AllAllowancesResponse, AllowanceResponse, BalanceResponse,
     DownloadLogoResponse, MarketingInfoResponse, MinterResponse, TokenInfoResponse,
 };
-use cw20_base::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use token::msg::{
+    ExecuteMsg, HooksResponse, InstantiateMsg, LockInfoResponse, QueryMsg, TokenHookMsg,
+    TotalSupplyAtResponse, TransferPolicyResponse,
+};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -17,6 +20,7 @@ fn main() {
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(TokenHookMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(BalanceResponse), &out_dir);
     export_schema(&schema_for!(TokenInfoResponse), &out_dir);
@@ -26,4 +30,8 @@ fn main() {
     export_schema(&schema_for!(AllAccountsResponse), &out_dir);
     export_schema(&schema_for!(MarketingInfoResponse), &out_dir);
     export_schema(&schema_for!(DownloadLogoResponse), &out_dir);
+    export_schema(&schema_for!(TotalSupplyAtResponse), &out_dir);
+    export_schema(&schema_for!(HooksResponse), &out_dir);
+    export_schema(&schema_for!(TransferPolicyResponse), &out_dir);
+    export_schema(&schema_for!(LockInfoResponse), &out_dir);
 }
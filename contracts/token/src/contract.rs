@@ -0,0 +1,798 @@
+use crate::error::ContractError;
+use crate::msg::{
+    ExecuteMsg, HooksResponse, InstantiateMsg, LockInfoResponse, QueryMsg, TokenHookMsg,
+    TotalSupplyAtResponse, TransferPolicyResponse,
+};
+use crate::state::{
+    ADMIN, BALANCE_SNAPSHOTS, EXEMPTIONS, HOOKS, LOCKS, TOTAL_SUPPLY_SNAPSHOT, TRANSFERS_ENABLED_AT,
+};
+use cosmwasm_std::{
+    to_binary, Addr, Api, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response,
+    StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg};
+use cw20_base::contract::{
+    execute as cw20_execute, instantiate as cw20_instantiate, query as cw20_query,
+};
+use cw20_base::msg::QueryMsg as Cw20QueryMsg;
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+use cw_utils::Scheduled;
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn instantiate(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let initial_holders = msg
+        .initial_balances
+        .iter()
+        .map(|coin| deps.api.addr_validate(&coin.address))
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    let admin = match &msg.admin {
+        Some(admin) => deps.api.addr_validate(admin)?,
+        None => info.sender.clone(),
+    };
+    ADMIN.save(deps.storage, &admin)?;
+    TRANSFERS_ENABLED_AT.save(deps.storage, &None)?;
+
+    let res = cw20_instantiate(deps.branch(), env.clone(), info, msg.into())?;
+    update_snapshots(deps.storage, &env, &initial_holders)?;
+
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::AddHook { addr } => execute_add_hook(deps, info, addr),
+        ExecuteMsg::RemoveHook { addr } => execute_remove_hook(deps, info, addr),
+        ExecuteMsg::UpdateTransferPolicy {
+            transfers_enabled_at,
+        } => execute_update_transfer_policy(deps, info, transfers_enabled_at),
+        ExecuteMsg::LockAddress { address, unlock_at } => {
+            execute_lock_address(deps, info, address, unlock_at)
+        }
+        ExecuteMsg::UnlockAddress { address } => execute_unlock_address(deps, info, address),
+        ExecuteMsg::UpdateExemptions { add, remove } => {
+            execute_update_exemptions(deps, info, add, remove)
+        }
+        msg => {
+            let msg = msg
+                .into_cw20()
+                .ok_or_else(|| StdError::generic_err("not a cw20 execute message"))?;
+            execute_cw20(deps, env, info, msg)
+        }
+    }
+}
+
+/// Executes `msg` with cw20-base, then records snapshots and notifies hooks of balance changes
+fn execute_cw20(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw20ExecuteMsg,
+) -> Result<Response, ContractError> {
+    if let Some(owner) = transferred_from(deps.api, &info.sender, &msg)? {
+        assert_transfer_allowed(deps.storage, &env, &owner)?;
+    }
+
+    let addresses = balance_changing_addresses(deps.api, &info.sender, &msg)?;
+    let old_balances = load_balances(deps.storage, &addresses)?;
+
+    let res = cw20_execute(deps.branch(), env.clone(), info, msg)?;
+    update_snapshots(deps.storage, &env, &addresses)?;
+
+    let new_balances = load_balances(deps.storage, &addresses)?;
+    let hook_msgs = balance_changed_hook_msgs(deps.storage, &old_balances, &new_balances)?;
+
+    Ok(res.add_submessages(hook_msgs))
+}
+
+fn assert_admin(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    if ADMIN.load(storage)? != *sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+fn execute_update_transfer_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    transfers_enabled_at: Option<Scheduled>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    TRANSFERS_ENABLED_AT.save(deps.storage, &transfers_enabled_at)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_transfer_policy")
+        .add_attribute(
+            "transfers_enabled_at",
+            transfers_enabled_at
+                .map(|enabled_at| enabled_at.to_string())
+                .unwrap_or_else(|| "none".to_string()),
+        ))
+}
+
+fn execute_lock_address(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    unlock_at: Scheduled,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    LOCKS.save(deps.storage, &address, &unlock_at)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "lock_address")
+        .add_attribute("address", address)
+        .add_attribute("unlock_at", unlock_at.to_string()))
+}
+
+fn execute_unlock_address(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    LOCKS.remove(deps.storage, &address);
+
+    Ok(Response::new()
+        .add_attribute("action", "unlock_address")
+        .add_attribute("address", address))
+}
+
+fn execute_update_exemptions(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    for address in &add {
+        EXEMPTIONS.save(deps.storage, &deps.api.addr_validate(address)?, &Empty {})?;
+    }
+
+    for address in &remove {
+        EXEMPTIONS.remove(deps.storage, &deps.api.addr_validate(address)?);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_exemptions")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string()))
+}
+
+fn execute_add_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    let hook = deps.api.addr_validate(&addr)?;
+    if HOOKS.has(deps.storage, &hook) {
+        return Err(ContractError::HookAlreadyRegistered {});
+    }
+    HOOKS.save(deps.storage, &hook, &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_hook")
+        .add_attribute("hook", hook))
+}
+
+fn execute_remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    let hook = deps.api.addr_validate(&addr)?;
+    if !HOOKS.has(deps.storage, &hook) {
+        return Err(ContractError::HookNotRegistered {});
+    }
+    HOOKS.remove(deps.storage, &hook);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_hook")
+        .add_attribute("hook", hook))
+}
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::BalanceAt { address, height } => query_balance_at(deps, address, height),
+        QueryMsg::TotalSupplyAt { height } => query_total_supply_at(deps, height),
+        QueryMsg::Hooks {} => query_hooks(deps),
+        QueryMsg::TransferPolicy {} => query_transfer_policy(deps),
+        QueryMsg::LockInfo { address } => query_lock_info(deps, env, address),
+        QueryMsg::Balance { address } => cw20_query(deps, env, Cw20QueryMsg::Balance { address }),
+        QueryMsg::TokenInfo {} => cw20_query(deps, env, Cw20QueryMsg::TokenInfo {}),
+        QueryMsg::Minter {} => cw20_query(deps, env, Cw20QueryMsg::Minter {}),
+        QueryMsg::Allowance { owner, spender } => {
+            cw20_query(deps, env, Cw20QueryMsg::Allowance { owner, spender })
+        }
+        QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => cw20_query(
+            deps,
+            env,
+            Cw20QueryMsg::AllAllowances {
+                owner,
+                start_after,
+                limit,
+            },
+        ),
+        QueryMsg::AllAccounts { start_after, limit } => {
+            cw20_query(deps, env, Cw20QueryMsg::AllAccounts { start_after, limit })
+        }
+        QueryMsg::MarketingInfo {} => cw20_query(deps, env, Cw20QueryMsg::MarketingInfo {}),
+        QueryMsg::DownloadLogo {} => cw20_query(deps, env, Cw20QueryMsg::DownloadLogo {}),
+    }
+}
+
+/// Returns the owner of the tokens moved by a transfer, send or transfer from
+fn transferred_from(api: &dyn Api, sender: &Addr, msg: &Cw20ExecuteMsg) -> StdResult<Option<Addr>> {
+    let owner = match msg {
+        Cw20ExecuteMsg::Transfer { .. } | Cw20ExecuteMsg::Send { .. } => Some(sender.clone()),
+        Cw20ExecuteMsg::TransferFrom { owner, .. } | Cw20ExecuteMsg::SendFrom { owner, .. } => {
+            Some(api.addr_validate(owner)?)
+        }
+        _ => None,
+    };
+
+    Ok(owner)
+}
+
+fn assert_transfer_allowed(
+    storage: &dyn Storage,
+    env: &Env,
+    owner: &Addr,
+) -> Result<(), ContractError> {
+    if EXEMPTIONS.has(storage, owner) {
+        return Ok(());
+    }
+
+    if let Some(enabled_at) = TRANSFERS_ENABLED_AT.load(storage)? {
+        if !enabled_at.is_triggered(&env.block) {
+            return Err(ContractError::TransfersDisabled { enabled_at });
+        }
+    }
+
+    if let Some(unlock_at) = LOCKS.may_load(storage, owner)? {
+        if !unlock_at.is_triggered(&env.block) {
+            return Err(ContractError::AddressLocked {
+                address: owner.to_string(),
+                unlock_at,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the addresses whose balance can be changed by `msg`
+fn balance_changing_addresses(
+    api: &dyn Api,
+    sender: &Addr,
+    msg: &Cw20ExecuteMsg,
+) -> StdResult<Vec<Addr>> {
+    let mut addresses = match msg {
+        Cw20ExecuteMsg::Transfer { recipient, .. } => {
+            vec![sender.clone(), api.addr_validate(recipient)?]
+        }
+        Cw20ExecuteMsg::Send { contract, .. } => {
+            vec![sender.clone(), api.addr_validate(contract)?]
+        }
+        Cw20ExecuteMsg::Burn { .. } => vec![sender.clone()],
+        Cw20ExecuteMsg::TransferFrom {
+            owner, recipient, ..
+        } => vec![api.addr_validate(owner)?, api.addr_validate(recipient)?],
+        Cw20ExecuteMsg::SendFrom {
+            owner, contract, ..
+        } => vec![api.addr_validate(owner)?, api.addr_validate(contract)?],
+        Cw20ExecuteMsg::BurnFrom { owner, .. } => vec![api.addr_validate(owner)?],
+        Cw20ExecuteMsg::Mint { recipient, .. } => vec![api.addr_validate(recipient)?],
+        _ => vec![],
+    };
+    // transfers to self change nothing, but must not be reported twice
+    addresses.dedup();
+
+    Ok(addresses)
+}
+
+fn load_balances(storage: &dyn Storage, addresses: &[Addr]) -> StdResult<Vec<(Addr, Uint128)>> {
+    addresses
+        .iter()
+        .map(|address| {
+            let balance = BALANCES.may_load(storage, address)?.unwrap_or_default();
+            Ok((address.clone(), balance))
+        })
+        .collect()
+}
+
+/// Returns a BalanceChanged submessage to every hook for each balance that changed
+fn balance_changed_hook_msgs(
+    storage: &dyn Storage,
+    old_balances: &[(Addr, Uint128)],
+    new_balances: &[(Addr, Uint128)],
+) -> StdResult<Vec<SubMsg>> {
+    let hooks = HOOKS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    let mut msgs = vec![];
+    for ((address, old_balance), (_, new_balance)) in old_balances.iter().zip(new_balances) {
+        if old_balance == new_balance {
+            continue;
+        }
+
+        let msg = to_binary(&TokenHookMsg::BalanceChanged {
+            address: address.to_string(),
+            old_balance: *old_balance,
+            new_balance: *new_balance,
+        })?;
+
+        for hook in &hooks {
+            msgs.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: hook.to_string(),
+                msg: msg.clone(),
+                funds: vec![],
+            }));
+        }
+    }
+
+    Ok(msgs)
+}
+
+/// Records the current balances of `addresses` and the total supply at the current height
+fn update_snapshots(storage: &mut dyn Storage, env: &Env, addresses: &[Addr]) -> StdResult<()> {
+    for address in addresses {
+        let balance = BALANCES.may_load(storage, address)?.unwrap_or_default();
+        BALANCE_SNAPSHOTS.save(storage, address, &balance, env.block.height)?;
+    }
+
+    let total_supply = TOKEN_INFO.load(storage)?.total_supply;
+    if TOTAL_SUPPLY_SNAPSHOT.may_load(storage)? != Some(total_supply) {
+        TOTAL_SUPPLY_SNAPSHOT.save(storage, &total_supply, env.block.height)?;
+    }
+
+    Ok(())
+}
+
+fn query_balance_at(deps: Deps, address: String, height: u64) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = BALANCE_SNAPSHOTS
+        .may_load_at_height(deps.storage, &address, height)?
+        .unwrap_or_default();
+
+    to_binary(&BalanceResponse { balance })
+}
+
+fn query_total_supply_at(deps: Deps, height: u64) -> StdResult<Binary> {
+    let total_supply = TOTAL_SUPPLY_SNAPSHOT
+        .may_load_at_height(deps.storage, height)?
+        .unwrap_or_default();
+
+    to_binary(&TotalSupplyAtResponse { total_supply })
+}
+
+fn query_hooks(deps: Deps) -> StdResult<Binary> {
+    let hooks = HOOKS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|hook| hook.map(String::from))
+        .collect::<StdResult<Vec<String>>>()?;
+
+    to_binary(&HooksResponse { hooks })
+}
+
+fn query_transfer_policy(deps: Deps) -> StdResult<Binary> {
+    let exemptions = EXEMPTIONS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|address| address.map(String::from))
+        .collect::<StdResult<Vec<String>>>()?;
+
+    to_binary(&TransferPolicyResponse {
+        transfers_enabled_at: TRANSFERS_ENABLED_AT.load(deps.storage)?,
+        exemptions,
+    })
+}
+
+fn query_lock_info(deps: Deps, env: Env, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+
+    to_binary(&LockInfoResponse {
+        unlock_at: LOCKS.may_load(deps.storage, &address)?,
+        is_exempted: EXEMPTIONS.has(deps.storage, &address),
+        can_transfer: assert_transfer_allowed(deps.storage, &env, &address).is_ok(),
+    })
+}
+
+#[cfg(test)]
+mod test_snapshot {
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, TotalSupplyAtResponse};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, Deps, Uint128};
+    use cw20::{BalanceResponse, Cw20Coin, MinterResponse};
+
+    const MINTER: &str = "minter";
+    const HOLDER: &str = "holder";
+
+    fn balance_at(deps: Deps, address: &str, height: u64) -> Uint128 {
+        let res: BalanceResponse = from_binary(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::BalanceAt {
+                    address: address.to_string(),
+                    height,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.balance
+    }
+
+    fn total_supply_at(deps: Deps, height: u64) -> Uint128 {
+        let res: TotalSupplyAtResponse =
+            from_binary(&query(deps, mock_env(), QueryMsg::TotalSupplyAt { height }).unwrap())
+                .unwrap();
+        res.total_supply
+    }
+
+    #[test]
+    fn test_balance_at() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let start = env.block.height;
+
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MINTER, &[]),
+            InstantiateMsg {
+                name: "channel".to_string(),
+                symbol: "CHANNEL".to_string(),
+                decimals: 6,
+                initial_balances: vec![Cw20Coin {
+                    address: MINTER.to_string(),
+                    amount: Uint128::new(1000),
+                }],
+                mint: Some(MinterResponse {
+                    minter: MINTER.to_string(),
+                    cap: None,
+                }),
+                marketing: None,
+                admin: None,
+            },
+        )
+        .unwrap();
+
+        env.block.height = start + 10;
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Transfer {
+                recipient: HOLDER.to_string(),
+                amount: Uint128::new(400),
+            },
+        )
+        .unwrap();
+
+        env.block.height = start + 20;
+        execute(
+            deps.as_mut(),
+            env,
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Mint {
+                recipient: HOLDER.to_string(),
+                amount: Uint128::new(100),
+            },
+        )
+        .unwrap();
+
+        // changes at a height are visible from the next height
+        assert_eq!(
+            balance_at(deps.as_ref(), MINTER, start + 1),
+            Uint128::new(1000)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), MINTER, start + 10),
+            Uint128::new(1000)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), MINTER, start + 11),
+            Uint128::new(600)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), HOLDER, start + 10),
+            Uint128::zero()
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), HOLDER, start + 11),
+            Uint128::new(400)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), HOLDER, start + 21),
+            Uint128::new(500)
+        );
+
+        assert_eq!(
+            total_supply_at(deps.as_ref(), start + 11),
+            Uint128::new(1000)
+        );
+        assert_eq!(
+            total_supply_at(deps.as_ref(), start + 21),
+            Uint128::new(1100)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_hooks {
+    use crate::contract::{execute, instantiate, query};
+    use crate::error::ContractError;
+    use crate::msg::{ExecuteMsg, HooksResponse, InstantiateMsg, QueryMsg, TokenHookMsg};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, to_binary, CosmosMsg, Uint128, WasmMsg};
+    use cw20::Cw20Coin;
+
+    const ADMIN: &str = "admin";
+    const HOLDER: &str = "holder";
+    const HOOK: &str = "hook";
+
+    #[test]
+    fn test_balance_changed_hook() {
+        let mut deps = mock_dependencies();
+
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN, &[]),
+            InstantiateMsg {
+                name: "channel".to_string(),
+                symbol: "CHANNEL".to_string(),
+                decimals: 6,
+                initial_balances: vec![Cw20Coin {
+                    address: HOLDER.to_string(),
+                    amount: Uint128::new(1000),
+                }],
+                mint: None,
+                marketing: None,
+                admin: None,
+            },
+        )
+        .unwrap();
+
+        let add_hook = ExecuteMsg::AddHook {
+            addr: HOOK.to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(HOLDER, &[]),
+            add_hook.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), add_hook).unwrap();
+
+        let hooks: HooksResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Hooks {}).unwrap()).unwrap();
+        assert_eq!(hooks.hooks, vec![HOOK.to_string()]);
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(HOLDER, &[]),
+            ExecuteMsg::Burn {
+                amount: Uint128::new(300),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HOOK.to_string(),
+                msg: to_binary(&TokenHookMsg::BalanceChanged {
+                    address: HOLDER.to_string(),
+                    old_balance: Uint128::new(1000),
+                    new_balance: Uint128::new(700),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN, &[]),
+            ExecuteMsg::RemoveHook {
+                addr: HOOK.to_string(),
+            },
+        )
+        .unwrap();
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(HOLDER, &[]),
+            ExecuteMsg::Transfer {
+                recipient: ADMIN.to_string(),
+                amount: Uint128::new(300),
+            },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_transfer_policy {
+    use crate::contract::{execute, instantiate, query};
+    use crate::error::ContractError;
+    use crate::msg::{ExecuteMsg, InstantiateMsg, LockInfoResponse, QueryMsg};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, Uint128};
+    use cw20::Cw20Coin;
+    use cw_utils::Scheduled;
+
+    const ADMIN: &str = "admin";
+    const HOLDER: &str = "holder";
+    const ICO: &str = "ico";
+
+    fn transfer(recipient: &str) -> ExecuteMsg {
+        ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(100),
+        }
+    }
+
+    #[test]
+    fn test_transfer_policy() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let enabled_at = Scheduled::AtHeight(env.block.height + 100);
+        let unlock_at = Scheduled::AtHeight(env.block.height + 200);
+
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADMIN, &[]),
+            InstantiateMsg {
+                name: "channel".to_string(),
+                symbol: "CHANNEL".to_string(),
+                decimals: 6,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: HOLDER.to_string(),
+                        amount: Uint128::new(1000),
+                    },
+                    Cw20Coin {
+                        address: ICO.to_string(),
+                        amount: Uint128::new(1000),
+                    },
+                ],
+                mint: None,
+                marketing: None,
+                admin: None,
+            },
+        )
+        .unwrap();
+
+        for msg in [
+            ExecuteMsg::UpdateTransferPolicy {
+                transfers_enabled_at: Some(enabled_at),
+            },
+            ExecuteMsg::LockAddress {
+                address: HOLDER.to_string(),
+                unlock_at,
+            },
+            ExecuteMsg::UpdateExemptions {
+                add: vec![ICO.to_string()],
+                remove: vec![],
+            },
+        ] {
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(HOLDER, &[]),
+                msg.clone(),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            execute(deps.as_mut(), env.clone(), mock_info(ADMIN, &[]), msg).unwrap();
+        }
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(HOLDER, &[]),
+            transfer(ADMIN),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TransfersDisabled { enabled_at });
+
+        // exempted addresses can always transfer
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ICO, &[]),
+            transfer(ADMIN),
+        )
+        .unwrap();
+
+        let mut enabled = env.clone();
+        enabled.block.height += 100;
+        let err = execute(
+            deps.as_mut(),
+            enabled.clone(),
+            mock_info(HOLDER, &[]),
+            transfer(ADMIN),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AddressLocked {
+                address: HOLDER.to_string(),
+                unlock_at
+            }
+        );
+
+        // other holders can transfer once transfers are enabled
+        execute(
+            deps.as_mut(),
+            enabled.clone(),
+            mock_info(ADMIN, &[]),
+            transfer(HOLDER),
+        )
+        .unwrap();
+
+        let lock_info: LockInfoResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                enabled,
+                QueryMsg::LockInfo {
+                    address: HOLDER.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(lock_info.unlock_at, Some(unlock_at));
+        assert!(!lock_info.can_transfer);
+
+        let mut unlocked = env;
+        unlocked.block.height += 200;
+        execute(
+            deps.as_mut(),
+            unlocked,
+            mock_info(HOLDER, &[]),
+            transfer(ADMIN),
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,30 @@
+use cosmwasm_std::StdError;
+use cw_utils::Scheduled;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Cw20(#[from] cw20_base::ContractError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Hook already registered")]
+    HookAlreadyRegistered {},
+
+    #[error("Hook not registered")]
+    HookNotRegistered {},
+
+    #[error("Transfers are disabled until {enabled_at}")]
+    TransfersDisabled { enabled_at: Scheduled },
+
+    #[error("Tokens of {address} are locked until {unlock_at}")]
+    AddressLocked {
+        address: String,
+        unlock_at: Scheduled,
+    },
+}
@@ -1,31 +1,4 @@
-use cosmwasm_std::{entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
-use cw20_base::contract::{
-    execute as cw20_execute, instantiate as cw20_instantiate, query as cw20_query,
-};
-use cw20_base::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use cw20_base::ContractError;
-
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn instantiate(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    msg: InstantiateMsg,
-) -> Result<Response, ContractError> {
-    cw20_instantiate(deps, env, info, msg)
-}
-
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    msg: ExecuteMsg,
-) -> Result<Response, ContractError> {
-    cw20_execute(deps, env, info, msg)
-}
-
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    cw20_query(deps, env, msg)
-}
+pub mod contract;
+pub mod error;
+pub mod msg;
+pub mod state;
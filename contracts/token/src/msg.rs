@@ -0,0 +1,266 @@
+use cosmwasm_std::{Binary, Uint128};
+use cw20::{Cw20Coin, Cw20ExecuteMsg, Expiration, Logo, MinterResponse};
+use cw20_base::msg::InstantiateMarketingInfo;
+use cw_utils::Scheduled;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Same as the cw20-base InstantiateMsg, so it can be instantiated with it as well
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub initial_balances: Vec<Cw20Coin>,
+    pub mint: Option<MinterResponse>,
+    pub marketing: Option<InstantiateMarketingInfo>,
+    /// admin managing hooks, instantiator if not set
+    pub admin: Option<String>,
+}
+
+impl From<InstantiateMsg> for cw20_base::msg::InstantiateMsg {
+    fn from(msg: InstantiateMsg) -> Self {
+        cw20_base::msg::InstantiateMsg {
+            name: msg.name,
+            symbol: msg.symbol,
+            decimals: msg.decimals,
+            initial_balances: msg.initial_balances,
+            mint: msg.mint,
+            marketing: msg.marketing,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+    },
+    Burn {
+        amount: Uint128,
+    },
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    SendFrom {
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    BurnFrom {
+        owner: String,
+        amount: Uint128,
+    },
+    Mint {
+        recipient: String,
+        amount: Uint128,
+    },
+    UpdateMarketing {
+        project: Option<String>,
+        description: Option<String>,
+        marketing: Option<String>,
+    },
+    UploadLogo(Logo),
+    /// only admin can register a contract notified of balance changes
+    AddHook {
+        addr: String,
+    },
+    /// only admin can remove a registered hook
+    RemoveHook {
+        addr: String,
+    },
+    /// only admin can disable transfers until `transfers_enabled_at`
+    UpdateTransferPolicy {
+        transfers_enabled_at: Option<Scheduled>,
+    },
+    /// only admin can lock tokens of an address until `unlock_at`
+    LockAddress {
+        address: String,
+        unlock_at: Scheduled,
+    },
+    /// only admin can unlock tokens of an address
+    UnlockAddress {
+        address: String,
+    },
+    /// only admin can update addresses exempted from the transfer policy and locks
+    UpdateExemptions {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+}
+
+impl ExecuteMsg {
+    /// Returns the cw20-base message handled by cw20-base itself
+    pub fn into_cw20(self) -> Option<Cw20ExecuteMsg> {
+        let msg = match self {
+            ExecuteMsg::Transfer { recipient, amount } => {
+                Cw20ExecuteMsg::Transfer { recipient, amount }
+            }
+            ExecuteMsg::Burn { amount } => Cw20ExecuteMsg::Burn { amount },
+            ExecuteMsg::Send {
+                contract,
+                amount,
+                msg,
+            } => Cw20ExecuteMsg::Send {
+                contract,
+                amount,
+                msg,
+            },
+            ExecuteMsg::IncreaseAllowance {
+                spender,
+                amount,
+                expires,
+            } => Cw20ExecuteMsg::IncreaseAllowance {
+                spender,
+                amount,
+                expires,
+            },
+            ExecuteMsg::DecreaseAllowance {
+                spender,
+                amount,
+                expires,
+            } => Cw20ExecuteMsg::DecreaseAllowance {
+                spender,
+                amount,
+                expires,
+            },
+            ExecuteMsg::TransferFrom {
+                owner,
+                recipient,
+                amount,
+            } => Cw20ExecuteMsg::TransferFrom {
+                owner,
+                recipient,
+                amount,
+            },
+            ExecuteMsg::SendFrom {
+                owner,
+                contract,
+                amount,
+                msg,
+            } => Cw20ExecuteMsg::SendFrom {
+                owner,
+                contract,
+                amount,
+                msg,
+            },
+            ExecuteMsg::BurnFrom { owner, amount } => Cw20ExecuteMsg::BurnFrom { owner, amount },
+            ExecuteMsg::Mint { recipient, amount } => Cw20ExecuteMsg::Mint { recipient, amount },
+            ExecuteMsg::UpdateMarketing {
+                project,
+                description,
+                marketing,
+            } => Cw20ExecuteMsg::UpdateMarketing {
+                project,
+                description,
+                marketing,
+            },
+            ExecuteMsg::UploadLogo(logo) => Cw20ExecuteMsg::UploadLogo(logo),
+            ExecuteMsg::AddHook { .. }
+            | ExecuteMsg::RemoveHook { .. }
+            | ExecuteMsg::UpdateTransferPolicy { .. }
+            | ExecuteMsg::LockAddress { .. }
+            | ExecuteMsg::UnlockAddress { .. }
+            | ExecuteMsg::UpdateExemptions { .. } => return None,
+        };
+
+        Some(msg)
+    }
+}
+
+/// Message sent to every hook contract when a balance changes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenHookMsg {
+    BalanceChanged {
+        address: String,
+        old_balance: Uint128,
+        new_balance: Uint128,
+    },
+}
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Balance {
+        address: String,
+    },
+    TokenInfo {},
+    Minter {},
+    Allowance {
+        owner: String,
+        spender: String,
+    },
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    MarketingInfo {},
+    DownloadLogo {},
+    /// balance at the beginning of the block `height`
+    BalanceAt {
+        address: String,
+        height: u64,
+    },
+    /// total supply at the beginning of the block `height`
+    TotalSupplyAt {
+        height: u64,
+    },
+    Hooks {},
+    TransferPolicy {},
+    LockInfo {
+        address: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TotalSupplyAtResponse {
+    pub total_supply: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HooksResponse {
+    pub hooks: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TransferPolicyResponse {
+    pub transfers_enabled_at: Option<Scheduled>,
+    pub exemptions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LockInfoResponse {
+    pub unlock_at: Option<Scheduled>,
+    pub is_exempted: bool,
+    /// whether the address can transfer at the current block
+    pub can_transfer: bool,
+}
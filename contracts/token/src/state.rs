@@ -0,0 +1,29 @@
+use cosmwasm_std::{Addr, Empty, Uint128};
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
+use cw_utils::Scheduled;
+
+pub const ADMIN: Item<Addr> = Item::new("admin");
+/// contracts notified of every balance change
+pub const HOOKS: Map<&Addr, Empty> = Map::new("hooks");
+/// transfers are disabled for everyone but exempted addresses until then
+pub const TRANSFERS_ENABLED_AT: Item<Option<Scheduled>> = Item::new("transfers_enabled_at");
+/// tokens of the address can't be transferred until the unlock time
+pub const LOCKS: Map<&Addr, Scheduled> = Map::new("locks");
+/// addresses allowed to transfer even if transfers are disabled or locked, like the ico
+/// and pair contracts
+pub const EXEMPTIONS: Map<&Addr, Empty> = Map::new("exemptions");
+
+/// Balances mirrored from cw20-base balances with their history
+pub const BALANCE_SNAPSHOTS: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "balance_snapshots",
+    "balance_snapshots__checkpoints",
+    "balance_snapshots__changelog",
+    Strategy::EveryBlock,
+);
+
+pub const TOTAL_SUPPLY_SNAPSHOT: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_supply_snapshot",
+    "total_supply_snapshot__checkpoints",
+    "total_supply_snapshot__changelog",
+    Strategy::EveryBlock,
+);
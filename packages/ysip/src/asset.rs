@@ -2,6 +2,7 @@ use crate::querier::{query_balance, query_token_balance, query_token_symbol};
 use cosmwasm_std::{Addr, Api, MessageInfo, QuerierWrapper, StdError, StdResult, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 const TOKEN_SYMBOL_MAX_LENGTH: usize = 10;
 
@@ -80,6 +81,15 @@ impl PartialEq for AssetInfo {
     }
 }
 
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetInfo::Token { contract_addr } => write!(f, "{}", contract_addr),
+            AssetInfo::NativeToken { denom } => write!(f, "{}", denom),
+        }
+    }
+}
+
 impl AssetInfo {
     pub fn check_is_valid(&self, api: &dyn Api) -> StdResult<()> {
         match self {
@@ -98,6 +108,13 @@ impl AssetInfo {
         Ok(())
     }
 
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            AssetInfo::Token { contract_addr } => contract_addr.as_bytes(),
+            AssetInfo::NativeToken { denom } => denom.as_bytes(),
+        }
+    }
+
     pub fn query_pool(&self, querier: &QuerierWrapper, pool_addr: &Addr) -> StdResult<Uint128> {
         match self {
             AssetInfo::Token { contract_addr } => {
@@ -110,6 +127,25 @@ impl AssetInfo {
     }
 }
 
+/// Returns the storage key of a pair, which is the same regardless of the asset order.
+/// Every asset is prefixed with its type and length, so different pairs never share a key
+pub fn pair_key(asset_infos: &[AssetInfo; 2]) -> Vec<u8> {
+    let mut asset_infos = asset_infos
+        .iter()
+        .map(|a| {
+            let tag = match a {
+                AssetInfo::Token { .. } => 0u8,
+                AssetInfo::NativeToken { .. } => 1u8,
+            };
+            let bytes = a.as_bytes();
+            [&[tag], &(bytes.len() as u32).to_be_bytes()[..], bytes].concat()
+        })
+        .collect::<Vec<Vec<u8>>>();
+    asset_infos.sort();
+
+    asset_infos.concat()
+}
+
 pub fn format_lp_token_name(
     asset_infos: [AssetInfo; 2],
     querier: &QuerierWrapper,
@@ -131,3 +167,42 @@ pub fn format_lp_token_name(
 
     Ok(format!("{}-{}-LP", short_symbols[0], short_symbols[1]).to_uppercase())
 }
+
+#[cfg(test)]
+mod test_asset {
+    use crate::asset::{pair_key, AssetInfo};
+    use cosmwasm_std::Addr;
+
+    fn token(addr: &str) -> AssetInfo {
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked(addr),
+        }
+    }
+
+    fn native(denom: &str) -> AssetInfo {
+        AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_pair_key() {
+        // the key does not depend on the asset order
+        assert_eq!(
+            pair_key(&[token("a"), native("ukrw")]),
+            pair_key(&[native("ukrw"), token("a")])
+        );
+
+        // a token and a native token with the same name
+        assert_ne!(
+            pair_key(&[token("x"), native("ukrw")]),
+            pair_key(&[native("x"), native("ukrw")])
+        );
+
+        // the same concatenated bytes split at a different position
+        assert_ne!(
+            pair_key(&[native("ab"), native("c")]),
+            pair_key(&[native("a"), native("bc")])
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use crate::asset::AssetInfo;
+use crate::pair::{PairInfo, PairType};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// code_id for pair contract
+    pub pair_code_id: u64,
+    /// code_id for the LP token of every pair
+    pub token_code_id: u64,
+    /// Address that recieves protocol fee of created pairs
+    pub protocol_fee_recipient: String,
+    /// in percent, passed as is to `ysip::pair::InstantiateMsg`
+    pub protocol_fee_percent: String,
+    /// in percent, passed as is to `ysip::pair::InstantiateMsg`
+    pub lp_fee_percent: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// CreatePair instantiates a new pair contract with the default fee config
+    CreatePair {
+        /// The assets of the pool, order does not matter
+        asset_infos: [AssetInfo; 2],
+        /// constant product if not set, stable pairs can only be created by the owner
+        pair_type: Option<PairType>,
+        /// initial amplification, required for stable pairs only
+        amp: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    Pair {
+        asset_infos: [AssetInfo; 2],
+    },
+    Pairs {
+        start_after: Option<[AssetInfo; 2]>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub pair_code_id: u64,
+    pub token_code_id: u64,
+    pub protocol_fee_recipient: String,
+    pub protocol_fee_percent: String,
+    pub lp_fee_percent: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PairsResponse {
+    pub pairs: Vec<PairInfo>,
+}
@@ -1,8 +1,8 @@
 use crate::asset::{Asset, AssetInfo};
-use cosmwasm_std::{Addr, Decimal, QuerierWrapper, StdError, StdResult, Uint128};
+use cosmwasm_std::{Addr, Decimal, QuerierWrapper, StdResult, Uint128};
+use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -12,8 +12,25 @@ pub struct InstantiateMsg {
     pub token_code_id: u64,
     /// Address that recieves protocol fee
     pub protocol_fee_recipient: String,
+    /// fee in percent, e.g. "0.15" is 0.15% of the offer amount, not 15%
     pub protocol_fee_percent: String,
+    /// fee in percent like `protocol_fee_percent`
     pub lp_fee_percent: String,
+    /// Owner allowed to update the fees, the instantiator if not set
+    pub owner: Option<String>,
+    pub pair_type: PairType,
+    /// Initial amplification coefficient, required for stable pairs only
+    pub amp: Option<u64>,
+}
+
+/// Curve the pool prices swaps with
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairType {
+    /// x * y = k
+    ConstantProduct {},
+    /// stableswap invariant for pegged assets, flat around the peg depending on the amplification
+    Stable {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -21,14 +38,16 @@ pub struct PairInfo {
     pub asset_infos: [AssetInfo; 2],
     pub contract_addr: Addr,
     pub liquidity_token: Addr,
+    pub pair_type: PairType,
 }
 
 impl PairInfo {
-    pub fn init(contract_addr: Addr, asset_infos: [AssetInfo; 2]) -> PairInfo {
+    pub fn init(contract_addr: Addr, asset_infos: [AssetInfo; 2], pair_type: PairType) -> PairInfo {
         Self {
             asset_infos,
             contract_addr,
             liquidity_token: Addr::unchecked(""),
+            pair_type,
         }
     }
 
@@ -53,15 +72,23 @@ impl PairInfo {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    /// Receive handles the CW20 hook messages sent with Cw20ExecuteMsg::Send
+    Receive(Cw20ReceiveMsg),
     /// ProvideLiquidity allows someone to provide liquidity in the pool
     ProvideLiquidity {
-        /// The assets available in the pool
+        /// The assets available in the pool, in any order
         assets: [Asset; 2],
+        /// max difference in percent between the deposit ratio and the pool ratio
+        slippage_tolerance: Option<String>,
     },
     /// Swap operation
     Swap {
         offer_asset: Asset,
+        /// fails if less than this amount is returned
         min_output_amount: Option<String>,
+        /// price of the offer asset per ask asset the trader expects, the spot price if not set
+        belief_price: Option<String>,
+        /// max spread in percent, `DEFAULT_MAX_SPREAD` if not set
         max_spread: Option<String>,
         to: Option<String>,
     },
@@ -69,13 +96,116 @@ pub enum ExecuteMsg {
     RemoveLiquidity {
         amount: Uint128,
     },
+    /// WithdrawLiquidity burns `amount` LP tokens like RemoveLiquidity, if `receive_asset` is set
+    /// the other side is swapped for it in the pool so only `receive_asset` is returned
+    WithdrawLiquidity {
+        amount: Uint128,
+        receive_asset: Option<AssetInfo>,
+        /// fails if less of `receive_asset` is returned, requires `receive_asset`. If not set, the
+        /// internal swap fails above `DEFAULT_MAX_SPREAD`
+        min_out: Option<Uint128>,
+    },
+    /// ProvideSingleSided swaps part of `asset` for the other side of the pool and provides
+    /// both sides as liquidity, the dust left of either side is refunded
+    ProvideSingleSided {
+        asset: Asset,
+        /// fails if less LP tokens are minted. If not set, the internal swap fails above
+        /// `DEFAULT_MAX_SPREAD`
+        min_lp_out: Option<Uint128>,
+    },
+    /// UpdateFees updates the given fee settings, can only be called by the owner.
+    /// Fees are in percent as in InstantiateMsg
+    UpdateFees {
+        protocol_fee_percent: Option<String>,
+        lp_fee_percent: Option<String>,
+        protocol_fee_recipient: Option<String>,
+    },
+    /// ProposeNewOwner starts an ownership transfer, which has to be accepted by the new owner
+    ProposeNewOwner {
+        owner: String,
+    },
+    /// AcceptOwnership completes the ownership transfer, can only be called by the proposed owner
+    AcceptOwnership {},
+    /// CollectProtocolFees sends the accrued protocol fees to the protocol fee recipient,
+    /// can only be called by the recipient
+    CollectProtocolFees {},
+    /// Sync adopts the actual balances of the pair, less the accrued protocol fees, as reserves
+    Sync {},
+    /// Skim sends the balances above the reserves and the accrued protocol fees to `to`,
+    /// can only be called by the owner
+    Skim {
+        to: String,
+    },
+    /// StartChangingAmp ramps the amplification of a stable pair linearly from its current value
+    /// to `next_amp` at `next_amp_time` in seconds, can only be called by the owner
+    StartChangingAmp {
+        next_amp: u64,
+        next_amp_time: u64,
+    },
+    /// StopChangingAmp keeps the current amplification of a stable pair,
+    /// can only be called by the owner
+    StopChangingAmp {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Swap the sent CW20 token for the other asset in the pool
+    Swap {
+        min_output_amount: Option<String>,
+        belief_price: Option<String>,
+        max_spread: Option<String>,
+        to: Option<String>,
+    },
+    /// Provide liquidity with the sent CW20 token as one side of the deposit
+    ProvideLiquidity {
+        /// The assets available in the pool, in any order
+        assets: [Asset; 2],
+        slippage_tolerance: Option<String>,
+    },
+    /// Provide liquidity with the sent CW20 token only, see `ExecuteMsg::ProvideSingleSided`
+    ProvideSingleSided { min_lp_out: Option<Uint128> },
+    /// Burn the sent LP token and withdraw the underlying assets,
+    /// see `ExecuteMsg::WithdrawLiquidity`
+    WithdrawLiquidity {
+        receive_asset: Option<AssetInfo>,
+        min_out: Option<Uint128>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     PairInfo {},
+    /// Config returns the owner and the fee settings
+    Config {},
+    /// AccruedProtocolFees returns the protocol fees not collected yet
+    AccruedProtocolFees {},
+    /// Amp returns the amplification of a stable pair and its ramp
+    Amp {},
+    /// ReserveDrift compares the tracked reserves with the actual balances of the pair
+    ReserveDrift {},
     Liquidity {},
+    /// Simulation returns the result of swapping `offer_asset` with the current reserves
+    Simulation {
+        offer_asset: Asset,
+    },
+    /// ReverseSimulation returns the offer amount needed to receive `ask_asset`
+    ReverseSimulation {
+        ask_asset: Asset,
+    },
+    /// SimulateProvideSingleSided returns the result of providing `asset` only
+    SimulateProvideSingleSided {
+        asset: Asset,
+    },
+    /// CumulativePrices returns the price accumulators as of the current block
+    CumulativePrices {},
+    /// Twap returns the time-weighted average prices over the last `window_seconds`. Only the
+    /// last 64 observations are kept, one per block with a reserve change, so it fails for
+    /// windows older than the oldest of them
+    Twap {
+        window_seconds: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -84,6 +214,56 @@ pub struct PairInfoResponse {
     pub assets: [AssetInfo; 2],
     pub contract_addr: Addr,
     pub liquidity_token: Addr,
+    pub pair_type: PairType,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigResponse {
+    pub owner: Addr,
+    /// proposed owner who has not accepted the ownership yet
+    pub pending_owner: Option<Addr>,
+    pub protocol_fee_recipient: Addr,
+    /// in percent, as in InstantiateMsg
+    pub protocol_fee_percent: Decimal,
+    /// in percent, as in InstantiateMsg
+    pub lp_fee_percent: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AmpResponse {
+    /// amplification at the current block
+    pub amp: u64,
+    pub init_amp: u64,
+    pub init_amp_time: u64,
+    pub next_amp: u64,
+    pub next_amp_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AccruedProtocolFeesResponse {
+    pub fees: [Asset; 2],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AssetDrift {
+    pub info: AssetInfo,
+    pub reserve: Uint128,
+    pub protocol_fee: Uint128,
+    pub balance: Uint128,
+    /// balance above the reserve and the accrued protocol fee
+    pub excess: Uint128,
+    /// reserve and accrued protocol fee not covered by the balance
+    pub deficit: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ReserveDriftResponse {
+    pub assets: [AssetDrift; 2],
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -98,72 +278,73 @@ pub struct LiquidityResponse {
     pub liquidity: [Asset; 2],
 }
 
-pub struct SwapParams {
-    pub offer_asset: Asset,
-    pub min_output_amount: Option<String>,
-    pub max_spread: Option<String>,
-    pub to: Option<Addr>,
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SimulationResponse {
+    pub return_amount: Uint128,
+    pub spread_amount: Uint128,
+    pub protocol_fee_amount: Uint128,
+    pub input_token_fee_amount: Uint128,
+    pub output_token_fee_amount: Uint128,
 }
 
-fn to_decimal(input: &Option<String>) -> StdResult<Decimal> {
-    Ok(Decimal::from_str(
-        &input.clone().unwrap_or("0".to_string()),
-    )?)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ReverseSimulationResponse {
+    pub offer_amount: Uint128,
+    /// amount actually received when offering `offer_amount`, never less than requested
+    pub return_amount: Uint128,
+    pub spread_amount: Uint128,
+    pub protocol_fee_amount: Uint128,
+    pub input_token_fee_amount: Uint128,
+    pub output_token_fee_amount: Uint128,
 }
 
-impl SwapParams {
-    pub fn assert_min_token_bought(&self, true_amount: Uint128) -> StdResult<()> {
-        let min_output_amount = to_decimal(&self.min_output_amount)?;
-        let max_spread = to_decimal(&self.max_spread)?;
-
-        let max_diff = min_output_amount
-            * Decimal::from_ratio(max_spread.atomics(), Uint128::new(10u128.pow(20)));
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SimulateProvideSingleSidedResponse {
+    pub lp_amount: Uint128,
+    /// part of the provided asset swapped for the other side
+    pub swap_amount: Uint128,
+    pub return_amount: Uint128,
+    pub protocol_fee_amount: Uint128,
+    /// dust of both sides left after the deposit, in the order of the pool
+    pub refund_assets: [Asset; 2],
+}
 
-        let true_amount = Decimal::new(true_amount * Uint128::new(10u128.pow(18)));
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CumulativePricesResponse {
+    pub assets: [Asset; 2],
+    /// price of assets[0] in assets[1] scaled by 10^18, accumulated per second
+    pub price0_cumulative_last: Uint128,
+    /// price of assets[1] in assets[0] scaled by 10^18, accumulated per second
+    pub price1_cumulative_last: Uint128,
+    pub block_time_last: u64,
+}
 
-        if true_amount >= max_diff {
-            Ok(())
-        } else {
-            Err(StdError::generic_err("not enough token bought"))
-        }
-    }
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TwapResponse {
+    /// average price of asset_infos[0] in asset_infos[1]
+    pub price0_average: Decimal,
+    /// average price of asset_infos[1] in asset_infos[0]
+    pub price1_average: Decimal,
+    /// seconds actually covered by the average, at least the requested window
+    pub window_seconds: u64,
 }
 
-#[cfg(test)]
-pub mod test_swap {
-    use crate::asset::{Asset, AssetInfo};
-    use crate::pair::SwapParams;
-    use cosmwasm_std::{Addr, Decimal, Uint128};
-    use std::str::FromStr;
-
-    #[test]
-    fn test() {
-        let sp = SwapParams {
-            offer_asset: Asset {
-                info: AssetInfo::Token {
-                    contract_addr: Addr::unchecked(""),
-                },
-                amount: Uint128::new(10000),
-            },
-            min_output_amount: Some("100".to_string()),
-            max_spread: Some("5".to_string()),
-            to: Some(Addr::unchecked("")),
-        };
-
-        // success
-        sp.assert_min_token_bought(Decimal::from_str("105").unwrap())
-            .unwrap();
-
-        // fail
-        sp.assert_min_token_bought(Decimal::from_str("106").unwrap())
-            .unwrap_err();
-
-        // success
-        sp.assert_min_token_sold(Decimal::from_str("95").unwrap())
-            .unwrap();
-
-        // fail
-        sp.assert_min_token_sold(Decimal::from_str("94").unwrap())
-            .unwrap_err();
-    }
+/// max spread in percent of swaps which do not set one
+pub const DEFAULT_MAX_SPREAD: u64 = 1;
+/// swaps cannot set a max spread above this percent
+pub const MAX_ALLOWED_SPREAD: u64 = 50;
+/// deposits cannot set a slippage tolerance above this percent
+pub const MAX_ALLOWED_SLIPPAGE: u64 = 50;
+
+pub struct SwapParams {
+    pub offer_asset: Asset,
+    pub min_output_amount: Option<String>,
+    pub belief_price: Option<String>,
+    pub max_spread: Option<String>,
+    pub to: Option<Addr>,
 }
@@ -0,0 +1,84 @@
+use crate::asset::AssetInfo;
+use cosmwasm_std::{Addr, Uint128};
+use cw20::Cw20ReceiveMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// factory used to look up the pair of every swap operation
+    pub factory: String,
+}
+
+/// A single swap through the pair of `offer_asset_info` and `ask_asset_info`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapOperation {
+    pub offer_asset_info: AssetInfo,
+    pub ask_asset_info: AssetInfo,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Receive handles the CW20 hook messages sent with Cw20ExecuteMsg::Send
+    Receive(Cw20ReceiveMsg),
+    /// ExecuteSwapOperations swaps the sent offer asset through every operation in order,
+    /// exactly one coin of the first offer asset has to be sent
+    ExecuteSwapOperations {
+        operations: Vec<SwapOperation>,
+        /// fails if the receiver gets less than this amount of the last ask asset, the single
+        /// swaps only fail above `ysip::pair::MAX_ALLOWED_SPREAD`
+        minimum_receive: Uint128,
+        /// receiver of the last ask asset, the sender if not set
+        to: Option<String>,
+    },
+    /// Swaps `offer_amount` of the offer asset, or the whole router balance of it if not set,
+    /// can only be called by the router itself
+    ExecuteSwapOperation {
+        operation: SwapOperation,
+        offer_amount: Option<Uint128>,
+        to: Option<String>,
+    },
+    /// Checks the balance increase of the receiver, can only be called by the router itself
+    AssertMinimumReceive {
+        asset_info: AssetInfo,
+        prev_balance: Uint128,
+        minimum_receive: Uint128,
+        receiver: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Swap the sent CW20 token through every operation in order
+    ExecuteSwapOperations {
+        operations: Vec<SwapOperation>,
+        minimum_receive: Uint128,
+        to: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    /// SimulateSwapOperations returns the amount received by swapping `offer_amount`
+    /// through every operation with the current reserves
+    SimulateSwapOperations {
+        offer_amount: Uint128,
+        operations: Vec<SwapOperation>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigResponse {
+    pub factory: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SimulateSwapOperationsResponse {
+    pub amount: Uint128,
+}
@@ -75,12 +75,16 @@ pub fn get_cw20_mint_msg(
 }
 
 pub fn get_fee_transfer_msg(recipient: &Addr, fee: Asset) -> StdResult<CosmosMsg> {
-    match fee.info {
+    get_asset_transfer_msg(recipient, fee)
+}
+
+pub fn get_asset_transfer_msg(recipient: &Addr, asset: Asset) -> StdResult<CosmosMsg> {
+    match asset.info {
         AssetInfo::Token { contract_addr } => {
-            get_cw20_transfer_msg(recipient, &contract_addr, fee.amount)
+            get_cw20_transfer_msg(recipient, &contract_addr, asset.amount)
         }
         AssetInfo::NativeToken { denom } => {
-            Ok(get_bank_transfer_to_msg(recipient, &denom, fee.amount))
+            Ok(get_bank_transfer_to_msg(recipient, &denom, asset.amount))
         }
     }
 }
@@ -102,3 +106,14 @@ pub fn get_burn_from_msg(
     }
     .into())
 }
+
+pub fn get_burn_msg(contract_addr: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    let msg = Cw20ExecuteMsg::Burn { amount };
+
+    Ok(WasmMsg::Execute {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&msg)?,
+        funds: vec![],
+    }
+    .into())
+}
@@ -1,9 +1,11 @@
-use cosmwasm_std::{coin, Addr, Attribute, Uint128};
+use cosmwasm_std::{coin, to_binary, Addr, Attribute, Uint128};
 use cw20::Cw20ExecuteMsg;
 use cw_multi_test::BasicApp;
 use testing_base::execute::execute_contract;
 use ysip::asset::{Asset, AssetInfo};
-use ysip::pair::ExecuteMsg;
+use ysip::factory::ExecuteMsg as FactoryExecuteMsg;
+use ysip::pair::{Cw20HookMsg, ExecuteMsg};
+use ysip::router::{Cw20HookMsg as RouterCw20HookMsg, SwapOperation};
 
 pub fn execute_mint(
     app: &mut BasicApp,
@@ -76,6 +78,7 @@ pub fn execute_provide_liquidity(
                 amount: Uint128::new(native_token_amount),
             },
         ],
+        slippage_tolerance: None,
     };
 
     execute_contract(
@@ -94,19 +97,19 @@ pub fn execute_swap_token_in(
     sender: &str,
     swap_amount_in: u128,
 ) -> Vec<Attribute> {
-    let swap_msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::Token {
-                contract_addr: Addr::unchecked(token_addr),
-            },
-            amount: Uint128::new(swap_amount_in),
-        },
-        min_output_amount: Some(String::from("50")),
-        max_spread: Some(String::from("100")),
-        to: Some(String::from(sender)),
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: contract_addr.to_string(),
+        amount: Uint128::new(swap_amount_in),
+        msg: to_binary(&Cw20HookMsg::Swap {
+            min_output_amount: Some(String::from("50")),
+            belief_price: None,
+            max_spread: Some(String::from("10")),
+            to: Some(String::from(sender)),
+        })
+        .unwrap(),
     };
 
-    execute_contract(app, contract_addr, &swap_msg, &[], sender).unwrap()
+    execute_contract(app, token_addr, &send_msg, &[], sender).unwrap()
 }
 
 pub fn execute_swap_coin_in(
@@ -123,6 +126,7 @@ pub fn execute_swap_coin_in(
             amount: Uint128::new(swap_amount_in),
         },
         min_output_amount: Some(String::from("100")),
+        belief_price: None,
         max_spread: Some(String::from("5")),
         to: Some(String::from(sender)),
     };
@@ -136,6 +140,25 @@ pub fn execute_swap_coin_in(
     ).unwrap()
 }
 
+pub fn execute_withdraw_liquidity(
+    app: &mut BasicApp,
+    contract_addr: &Addr,
+    lp_token_addr: &Addr,
+    sender: &str,
+    amount: Uint128,
+) -> Vec<Attribute> {
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: contract_addr.to_string(),
+        amount,
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            receive_asset: None,
+            min_out: None,
+        }).unwrap(),
+    };
+
+    execute_contract(app, lp_token_addr, &send_msg, &[], sender).unwrap()
+}
+
 pub fn execute_remove_liquidity(
     app: &mut BasicApp,
     contract_addr: &Addr,
@@ -153,4 +176,41 @@ pub fn execute_remove_liquidity(
         &[],
         sender,
     ).unwrap()
-}
\ No newline at end of file
+}
+pub fn execute_create_pair(
+    app: &mut BasicApp,
+    factory_addr: &Addr,
+    asset_infos: [AssetInfo; 2],
+    sender: &str,
+) -> Result<Vec<Attribute>, anyhow::Error> {
+    let create_pair_msg = FactoryExecuteMsg::CreatePair {
+        asset_infos,
+        pair_type: None,
+        amp: None,
+    };
+
+    execute_contract(app, factory_addr, &create_pair_msg, &[], sender)
+}
+
+pub fn execute_swap_operations(
+    app: &mut BasicApp,
+    router_addr: &Addr,
+    token_addr: &Addr,
+    operations: Vec<SwapOperation>,
+    minimum_receive: u128,
+    sender: &str,
+    amount: u128,
+) -> Result<Vec<Attribute>, anyhow::Error> {
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: router_addr.to_string(),
+        amount: Uint128::new(amount),
+        msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+            operations,
+            minimum_receive: Uint128::new(minimum_receive),
+            to: None,
+        })
+        .unwrap(),
+    };
+
+    execute_contract(app, token_addr, &send_msg, &[], sender)
+}
@@ -2,7 +2,11 @@ use cosmwasm_std::Empty;
 use cw_multi_test::{Contract, ContractWrapper};
 
 pub fn mock_cw20_contract() -> Box<dyn Contract<Empty>> {
-    let contract = ContractWrapper::new(token::execute, token::instantiate, token::query);
+    let contract = ContractWrapper::new(
+        token::contract::execute,
+        token::contract::instantiate,
+        token::contract::query,
+    );
     Box::new(contract)
 }
 
@@ -24,3 +28,22 @@ pub fn mock_ico_contract() -> Box<dyn Contract<Empty>> {
     ).with_reply(ico::contract::reply);
     Box::new(contract)
 }
+
+pub fn mock_factory_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        factory::contract::execute,
+        factory::contract::instantiate,
+        factory::contract::query,
+    )
+    .with_reply(factory::contract::reply);
+    Box::new(contract)
+}
+
+pub fn mock_router_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        router::contract::execute,
+        router::contract::instantiate,
+        router::contract::query,
+    );
+    Box::new(contract)
+}
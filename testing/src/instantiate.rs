@@ -4,7 +4,9 @@ use cw20_base::msg::InstantiateMsg;
 use cw_multi_test::BasicApp;
 use testing_base::instantiate::instantiate_contract;
 use ysip::asset::AssetInfo;
-use ysip::pair::InstantiateMsg as PairInstantiateMsg;
+use ysip::factory::InstantiateMsg as FactoryInstantiateMsg;
+use ysip::pair::{InstantiateMsg as PairInstantiateMsg, PairType};
+use ysip::router::InstantiateMsg as RouterInstantiateMsg;
 
 pub fn instantiate_cw20_contract(
     app: &mut BasicApp,
@@ -55,6 +57,9 @@ pub fn instantiate_pair_contract(
         protocol_fee_recipient: admin.to_string(),
         protocol_fee_percent: "0.15".to_string(),
         lp_fee_percent: "0.15".to_string(),
+        owner: None,
+        pair_type: PairType::ConstantProduct {},
+        amp: None,
     };
 
     instantiate_contract(
@@ -67,3 +72,54 @@ pub fn instantiate_pair_contract(
         label,
     )
 }
+
+pub fn instantiate_factory_contract(
+    app: &mut BasicApp,
+    factory_code_id: u64,
+    pair_code_id: u64,
+    token_code_id: u64,
+    sender: &str,
+    admin: &str,
+    label: &str,
+) -> Addr {
+    let factory_init_msg = FactoryInstantiateMsg {
+        pair_code_id,
+        token_code_id,
+        protocol_fee_recipient: admin.to_string(),
+        protocol_fee_percent: "0.15".to_string(),
+        lp_fee_percent: "0.15".to_string(),
+    };
+
+    instantiate_contract(
+        app,
+        factory_init_msg,
+        &[],
+        factory_code_id,
+        sender,
+        admin,
+        label,
+    )
+}
+
+pub fn instantiate_router_contract(
+    app: &mut BasicApp,
+    router_code_id: u64,
+    factory_addr: &Addr,
+    sender: &str,
+    admin: &str,
+    label: &str,
+) -> Addr {
+    let router_init_msg = RouterInstantiateMsg {
+        factory: factory_addr.to_string(),
+    };
+
+    instantiate_contract(
+        app,
+        router_init_msg,
+        &[],
+        router_code_id,
+        sender,
+        admin,
+        label,
+    )
+}
@@ -2,3 +2,4 @@ pub mod execute;
 pub mod init;
 pub mod instantiate;
 pub mod query;
+pub mod setup;
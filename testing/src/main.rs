@@ -1,14 +1,37 @@
-use cosmwasm_std::{Addr, BankMsg, BlockInfo, coin, CosmosMsg, Uint128};
-use cw_multi_test::{Executor};
-use testing::execute::{execute_mint, execute_provide_liquidity, execute_remove_liquidity, execute_swap_token_in, increase_allowance};
-use testing::init::{mock_cw20_contract, mock_ico_contract, mock_pair_contract};
-use testing::instantiate::{instantiate_cw20_contract, instantiate_pair_contract};
+use cosmwasm_std::{Addr, BankMsg, BlockInfo, coin, coins, CosmosMsg, Decimal, to_binary, Uint128};
+use cw20::Cw20ExecuteMsg;
+use cw_multi_test::{BasicApp, Executor};
+use cw_utils::{Expiration, Scheduled};
+use testing::execute::{execute_create_pair, execute_mint, execute_provide_liquidity, execute_remove_liquidity, execute_swap_operations, execute_swap_token_in, execute_withdraw_liquidity, increase_allowance};
+use testing::init::{
+    mock_cw20_contract, mock_factory_contract, mock_ico_contract, mock_pair_contract,
+    mock_router_contract,
+};
+use testing::instantiate::{
+    instantiate_cw20_contract, instantiate_factory_contract, instantiate_pair_contract,
+    instantiate_router_contract,
+};
 use testing::query::{query_cw20_balance, query_pair_info};
+use testing::setup::{assert_no_drift, provide_initial_liquidity, setup_pair, setup_pair_with_liquidity};
 use testing_base::consts::{ADDR1, ADDR2, ADDR3};
 use testing_base::execute::execute_contract;
 use testing_base::init::init_app;
 use testing_base::instantiate::instantiate_contract;
+use ysip::asset::{Asset, AssetInfo};
+use ysip::factory::{ExecuteMsg as FactoryExecuteMsg, PairsResponse, QueryMsg as FactoryQueryMsg};
+use ysip::pair::{
+    AccruedProtocolFeesResponse, AmpResponse, ConfigResponse as PairConfigResponse, Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg, LiquidityResponse,
+    PairInfo, PairType, QueryMsg as PairQueryMsg, ReserveDriftResponse, SimulateProvideSingleSidedResponse, SimulationResponse, TwapResponse,
+};
+use ysip::router::{
+    ExecuteMsg as RouterExecuteMsg, QueryMsg as RouterQueryMsg, SimulateSwapOperationsResponse,
+    SwapOperation,
+};
 use ico::msg::{FundingAmountResponse, IsFundingFinishedResponse, QueryMsg, TokenAddressResponse, TotalFundingAmountResponse};
+use ico::msg::{PairAddressResponse, PendingRevenueResponse, PoolConfigMsg, RevenueHistoryResponse, VestingInfoResponse};
+use ico::state::VestingSchedule;
+use token::msg::{QueryMsg as TokenQueryMsg, TransferPolicyResponse};
+use std::str::FromStr;
 
 fn basic_test() {
     let mut app = init_app(ADDR1);
@@ -142,13 +165,23 @@ fn ico_test() {
     let ico_code_id = app.store_code(mock_ico_contract());
 
     let instantiate_msg = ico::msg::InstantiateMsg {
-        target_funding: Uint128::new(500),
-        deadline: 123_46,
+        soft_cap: Uint128::new(500),
+        hard_cap: Uint128::new(500),
+        funding_asset: AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+        start: None,
+        deadline: Expiration::AtHeight(12_346),
         token_code_id: channel_a_code_id,
         token_name: "channel".to_string(),
         token_symbol: "CHANNEL".to_string(),
         channel_token_amount: Uint128::new(1000000),
-        recipient: ADDR3.to_string()
+        recipient: ADDR3.to_string(),
+        pool: None,
+        vesting: None,
+        whitelist: None,
+        min_contribution: None,
+        max_contribution_per_address: None,
     };
 
     app.execute(
@@ -207,6 +240,29 @@ fn ico_test() {
     ).unwrap();
     println!("{:?}", res);
 
+    execute_contract(
+        &mut app,
+        &addr,
+        &ico::msg::ExecuteMsg::ClaimTokens {},
+        &[],
+        ADDR1,
+    ).unwrap();
+
+    // ADDR1 is already paid, so only ADDR2 receives tokens
+    let res = execute_contract(
+        &mut app,
+        &addr,
+        &ico::msg::ExecuteMsg::DistributeBatch { limit: None },
+        &[],
+        ADDR1,
+    ).unwrap();
+    println!("{:?}", res);
+
+    let token_addr: TokenAddressResponse = app.wrap().query_wasm_smart(addr.clone(), &ico::msg::QueryMsg::TokenAddress {}).unwrap();
+    let token_addr = Addr::unchecked(token_addr.address);
+    assert_eq!(query_cw20_balance(&app, &token_addr, ADDR1), Uint128::new(500000));
+    assert_eq!(query_cw20_balance(&app, &token_addr, ADDR2), Uint128::new(500000));
+
     let b = app.wrap().query_balance(ADDR1, "ukrw").unwrap();
     println!("{:?}", b);
 
@@ -240,6 +296,15 @@ fn ico_test() {
     let b = app.wrap().query_balance(ADDR2, "ukrw").unwrap();
     println!("ADDR2 balance: {:?}", b);
 
+    // the channel token reports the transfer to the ico through the balance hook
+    execute_contract(
+        &mut app,
+        &Addr::unchecked(&token_addr.address),
+        &cw20::Cw20ExecuteMsg::Transfer { recipient: ADDR3.to_string(), amount: Uint128::new(250000) },
+        &[],
+        ADDR2,
+    ).unwrap();
+
     let res = execute_contract(
         &mut app,
         &addr,
@@ -250,15 +315,1092 @@ fn ico_test() {
 
     println!("{:?}", res);
 
+    let pending: PendingRevenueResponse = app.wrap().query_wasm_smart(addr.clone(), &QueryMsg::PendingRevenue { addr: ADDR2.to_string() }).unwrap();
+    assert_eq!(pending.amount, Uint128::new(25000));
+
+    let pending: PendingRevenueResponse = app.wrap().query_wasm_smart(addr.clone(), &QueryMsg::PendingRevenue { addr: ADDR3.to_string() }).unwrap();
+    assert_eq!(pending.amount, Uint128::new(25000));
+
+    execute_contract(
+        &mut app,
+        &addr,
+        &ico::msg::ExecuteMsg::ClaimRevenue {},
+        &[],
+        ADDR2,
+    ).unwrap();
+
     let b = app.wrap().query_balance(ADDR2, "ukrw").unwrap();
     println!("ADDR2 balance: {:?}", b);
 
+    let history: RevenueHistoryResponse = app.wrap().query_wasm_smart(addr.clone(), &QueryMsg::RevenueHistory { start_after: None, limit: None }).unwrap();
+    assert_eq!(history.revenues.len(), 1);
+
     let res: TotalFundingAmountResponse = app.wrap().query_wasm_smart(addr, &QueryMsg::TotalFundingAmount {}).unwrap();
     println!("{:?}", res);
 
 }
 
+fn ico_pool_test() {
+    let mut app = init_app(ADDR1);
+    let token_code_id = app.store_code(mock_cw20_contract());
+    let pair_code_id = app.store_code(mock_pair_contract());
+    let ico_code_id = app.store_code(mock_ico_contract());
+
+    let instantiate_msg = ico::msg::InstantiateMsg {
+        soft_cap: Uint128::new(100000),
+        hard_cap: Uint128::new(100000),
+        funding_asset: AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+        start: None,
+        deadline: Expiration::AtHeight(12_346),
+        token_code_id,
+        token_name: "channel".to_string(),
+        token_symbol: "CHANNEL".to_string(),
+        channel_token_amount: Uint128::new(1000000),
+        recipient: ADDR3.to_string(),
+        pool: Some(PoolConfigMsg {
+            pair_code_id,
+            funding_ratio: Decimal::percent(20),
+            channel_token_amount: Uint128::new(200000),
+            protocol_fee_recipient: ADDR1.to_string(),
+            protocol_fee_percent: "0.15".to_string(),
+            lp_fee_percent: "0.15".to_string(),
+            treasury: Some(ADDR3.to_string()),
+        }),
+        vesting: None,
+        whitelist: None,
+        min_contribution: None,
+        max_contribution_per_address: None,
+    };
+
+    let addr = instantiate_contract(
+        &mut app,
+        instantiate_msg,
+        &[],
+        ico_code_id,
+        ADDR1,
+        ADDR1,
+        "ico",
+    );
+
+    execute_contract(
+        &mut app,
+        &addr,
+        &ico::msg::ExecuteMsg::FundChannelToken {},
+        &[coin(100000, "ukrw")],
+        ADDR1,
+    )
+    .unwrap();
+
+    let res = execute_contract(
+        &mut app,
+        &addr,
+        &ico::msg::ExecuteMsg::EndFunding {},
+        &[],
+        ADDR1,
+    )
+    .unwrap();
+    println!("{:?}", res);
+
+    let pair_addr: PairAddressResponse = app
+        .wrap()
+        .query_wasm_smart(addr.clone(), &QueryMsg::PairAddress {})
+        .unwrap();
+    let pair_addr = Addr::unchecked(pair_addr.address);
+
+    let liquidity: LiquidityResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_addr, &PairQueryMsg::Liquidity {})
+        .unwrap();
+    println!("{:?}", liquidity);
+    assert_eq!(liquidity.liquidity[0].amount, Uint128::new(200000));
+    assert_eq!(liquidity.liquidity[1].amount, Uint128::new(20000));
+
+    let pair_info = query_pair_info(&app, &pair_addr);
+    let treasury_lp_balance = query_cw20_balance(&app, &pair_info.liquidity_token, ADDR3);
+    println!("treasury lp balance: {}", treasury_lp_balance);
+    assert!(!treasury_lp_balance.is_zero());
+
+    // the channel tokens seeded into the pool don't earn revenue, funders get all of it
+    execute_contract(&mut app, &addr, &ico::msg::ExecuteMsg::ClaimTokens {}, &[], ADDR1).unwrap();
+    execute_contract(
+        &mut app,
+        &addr,
+        &ico::msg::ExecuteMsg::Allocation { amount: Uint128::new(100000) },
+        &[coin(100000, "ukrw")],
+        ADDR1,
+    ).unwrap();
+
+    let pending: PendingRevenueResponse = app.wrap().query_wasm_smart(addr.clone(), &QueryMsg::PendingRevenue { addr: pair_addr.to_string() }).unwrap();
+    assert_eq!(pending.amount, Uint128::zero());
+    let pending: PendingRevenueResponse = app.wrap().query_wasm_smart(addr.clone(), &QueryMsg::PendingRevenue { addr: ADDR1.to_string() }).unwrap();
+    assert_eq!(pending.amount, Uint128::new(100000));
+}
+
+fn ico_channel_token_policy_test() {
+    let mut app = init_app(ADDR1);
+    let token_code_id = app.store_code(mock_cw20_contract());
+    let pair_code_id = app.store_code(mock_pair_contract());
+    let ico_code_id = app.store_code(mock_ico_contract());
+
+    let instantiate_msg = ico::msg::InstantiateMsg {
+        soft_cap: Uint128::new(100000),
+        hard_cap: Uint128::new(100000),
+        funding_asset: AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+        start: None,
+        deadline: Expiration::AtHeight(12_346),
+        token_code_id,
+        token_name: "channel".to_string(),
+        token_symbol: "CHANNEL".to_string(),
+        channel_token_amount: Uint128::new(1000000),
+        recipient: ADDR3.to_string(),
+        pool: Some(PoolConfigMsg {
+            pair_code_id,
+            funding_ratio: Decimal::percent(20),
+            channel_token_amount: Uint128::new(200000),
+            protocol_fee_recipient: ADDR1.to_string(),
+            protocol_fee_percent: "0.15".to_string(),
+            lp_fee_percent: "0.15".to_string(),
+            treasury: Some(ADDR3.to_string()),
+        }),
+        vesting: None,
+        whitelist: None,
+        min_contribution: None,
+        max_contribution_per_address: None,
+    };
+    let addr = instantiate_contract(&mut app, instantiate_msg, &[], ico_code_id, ADDR1, ADDR1, "ico");
+
+    // the channel token does not exist before funding ends
+    let lock_msg = ico::msg::ExecuteMsg::LockAddress { address: ADDR2.to_string(), unlock_at: Scheduled::AtHeight(12_445) };
+    execute_contract(&mut app, &addr, &lock_msg, &[], ADDR1).unwrap_err();
+
+    app.send_tokens(Addr::unchecked(ADDR1), Addr::unchecked(ADDR2), &[coin(100000, "ukrw")]).unwrap();
+    execute_contract(&mut app, &addr, &ico::msg::ExecuteMsg::FundChannelToken {}, &[coin(100000, "ukrw")], ADDR2).unwrap();
+    execute_contract(&mut app, &addr, &ico::msg::ExecuteMsg::EndFunding {}, &[], ADDR1).unwrap();
+    execute_contract(&mut app, &addr, &ico::msg::ExecuteMsg::ClaimTokens {}, &[], ADDR2).unwrap();
+
+    let token_addr: TokenAddressResponse = app.wrap().query_wasm_smart(addr.clone(), &QueryMsg::TokenAddress {}).unwrap();
+    let token_addr = Addr::unchecked(token_addr.address);
+    let pair_addr: PairAddressResponse = app.wrap().query_wasm_smart(addr.clone(), &QueryMsg::PairAddress {}).unwrap();
+    let pair_addr = Addr::unchecked(pair_addr.address);
+
+    // the ico, the pair and the treasury are exempted from the start
+    let policy: TransferPolicyResponse = app.wrap().query_wasm_smart(token_addr.clone(), &TokenQueryMsg::TransferPolicy {}).unwrap();
+    for exempted in [addr.to_string(), pair_addr.to_string(), ADDR3.to_string()] {
+        assert!(policy.exemptions.contains(&exempted));
+    }
+
+    // only the ico admin can lock through the ico
+    execute_contract(&mut app, &addr, &lock_msg, &[], ADDR2).unwrap_err();
+    execute_contract(&mut app, &addr, &lock_msg, &[], ADDR1).unwrap();
+
+    let transfer_msg = Cw20ExecuteMsg::Transfer { recipient: ADDR1.to_string(), amount: Uint128::new(1000) };
+    execute_contract(&mut app, &token_addr, &transfer_msg, &[], ADDR2).unwrap_err();
+
+    // swaps out of the pool keep working while transfers are disabled
+    let policy_msg = ico::msg::ExecuteMsg::UpdateTransferPolicy { transfers_enabled_at: Some(Scheduled::AtHeight(12_395)) };
+    execute_contract(&mut app, &addr, &policy_msg, &[], ADDR1).unwrap();
+    let swap_msg = PairExecuteMsg::Swap {
+        offer_asset: Asset { info: AssetInfo::NativeToken { denom: "ukrw".to_string() }, amount: Uint128::new(1000) },
+        min_output_amount: None,
+        belief_price: None,
+        max_spread: Some("10".to_string()),
+        to: None,
+    };
+    execute_contract(&mut app, &pair_addr, &swap_msg, &[coin(1000, "ukrw")], ADDR1).unwrap();
+    execute_contract(&mut app, &token_addr, &transfer_msg, &[], ADDR1).unwrap_err();
+
+    // transfers are enabled again but the lock still holds
+    app.update_block(|block| block.height += 50);
+    execute_contract(&mut app, &token_addr, &transfer_msg, &[], ADDR1).unwrap();
+    execute_contract(&mut app, &token_addr, &transfer_msg, &[], ADDR2).unwrap_err();
+
+    let unlock_msg = ico::msg::ExecuteMsg::UnlockAddress { address: ADDR2.to_string() };
+    execute_contract(&mut app, &addr, &unlock_msg, &[], ADDR1).unwrap();
+    execute_contract(&mut app, &token_addr, &transfer_msg, &[], ADDR2).unwrap();
+}
+
+fn ico_vesting_test() {
+    let mut app = init_app(ADDR1);
+    let token_code_id = app.store_code(mock_cw20_contract());
+    let ico_code_id = app.store_code(mock_ico_contract());
+
+    let instantiate_msg = ico::msg::InstantiateMsg {
+        soft_cap: Uint128::new(500),
+        hard_cap: Uint128::new(500),
+        funding_asset: AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+        start: None,
+        deadline: Expiration::AtHeight(12_346),
+        token_code_id,
+        token_name: "channel".to_string(),
+        token_symbol: "CHANNEL".to_string(),
+        channel_token_amount: Uint128::new(1000000),
+        recipient: ADDR3.to_string(),
+        pool: None,
+        vesting: Some(VestingSchedule::Height {
+            cliff: 10,
+            duration: 100,
+        }),
+        whitelist: None,
+        min_contribution: None,
+        max_contribution_per_address: None,
+    };
+
+    let addr = instantiate_contract(
+        &mut app,
+        instantiate_msg,
+        &[],
+        ico_code_id,
+        ADDR1,
+        ADDR1,
+        "ico",
+    );
+
+    execute_contract(
+        &mut app,
+        &addr,
+        &ico::msg::ExecuteMsg::FundChannelToken {},
+        &[coin(500, "ukrw")],
+        ADDR1,
+    )
+    .unwrap();
+
+    execute_contract(
+        &mut app,
+        &addr,
+        &ico::msg::ExecuteMsg::EndFunding {},
+        &[],
+        ADDR1,
+    )
+    .unwrap();
+
+    // nothing is claimable before the cliff
+    execute_contract(
+        &mut app,
+        &addr,
+        &ico::msg::ExecuteMsg::ClaimVested {},
+        &[],
+        ADDR1,
+    )
+    .unwrap_err();
+
+    app.update_block(|block| block.height += 50);
+
+    let vesting_info: VestingInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            addr.clone(),
+            &QueryMsg::VestingInfo {
+                addr: ADDR1.to_string(),
+            },
+        )
+        .unwrap();
+    println!("{:?}", vesting_info);
+    assert_eq!(vesting_info.claimable, Uint128::new(500000));
+
+    execute_contract(
+        &mut app,
+        &addr,
+        &ico::msg::ExecuteMsg::ClaimVested {},
+        &[],
+        ADDR1,
+    )
+    .unwrap();
+
+    let token_addr: TokenAddressResponse = app
+        .wrap()
+        .query_wasm_smart(addr, &QueryMsg::TokenAddress {})
+        .unwrap();
+    let balance = query_cw20_balance(&app, &Addr::unchecked(token_addr.address), ADDR1);
+    assert_eq!(balance, Uint128::new(500000));
+}
+
+fn factory_test() {
+    let mut app = init_app(ADDR1);
+    let token_code_id = app.store_code(mock_cw20_contract());
+    let pair_code_id = app.store_code(mock_pair_contract());
+    let factory_code_id = app.store_code(mock_factory_contract());
+
+    let factory_addr = instantiate_factory_contract(
+        &mut app,
+        factory_code_id,
+        pair_code_id,
+        token_code_id,
+        ADDR1,
+        ADDR1,
+        "factory",
+    );
+
+    let channel_a_contract_addr = instantiate_cw20_contract(
+        &mut app,
+        token_code_id,
+        &[],
+        ADDR1,
+        ADDR1,
+        "channel_a",
+        "channel-a",
+        vec![],
+        "channel_a",
+    );
+
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: channel_a_contract_addr,
+        },
+        AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+    ];
+
+    let res = execute_create_pair(&mut app, &factory_addr, asset_infos.clone(), ADDR1).unwrap();
+    println!("{:?}", res);
+
+    // the same pair in the reverse order is rejected
+    let reversed_asset_infos = [asset_infos[1].clone(), asset_infos[0].clone()];
+    execute_create_pair(&mut app, &factory_addr, reversed_asset_infos.clone(), ADDR1).unwrap_err();
+
+    let pair: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &factory_addr,
+            &FactoryQueryMsg::Pair {
+                asset_infos: reversed_asset_infos,
+            },
+        )
+        .unwrap();
+    println!("{:?}", pair);
+
+    let pairs: PairsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &factory_addr,
+            &FactoryQueryMsg::Pairs {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(pairs.pairs, vec![pair.clone()]);
+
+    // the factory owner owns the created pairs
+    let update_fees_msg = PairExecuteMsg::UpdateFees {
+        protocol_fee_percent: Some("0.1".to_string()),
+        lp_fee_percent: Some("0.25".to_string()),
+        protocol_fee_recipient: Some(ADDR3.to_string()),
+    };
+    execute_contract(&mut app, &pair.contract_addr, &update_fees_msg, &[], ADDR2).unwrap_err();
+    execute_contract(&mut app, &pair.contract_addr, &update_fees_msg, &[], ADDR1).unwrap();
+
+    // fees are bounded
+    execute_contract(
+        &mut app,
+        &pair.contract_addr,
+        &PairExecuteMsg::UpdateFees {
+            protocol_fee_percent: None,
+            lp_fee_percent: Some("5".to_string()),
+            protocol_fee_recipient: None,
+        },
+        &[],
+        ADDR1,
+    )
+    .unwrap_err();
+
+    execute_contract(
+        &mut app,
+        &pair.contract_addr,
+        &PairExecuteMsg::ProposeNewOwner {
+            owner: ADDR2.to_string(),
+        },
+        &[],
+        ADDR1,
+    )
+    .unwrap();
+    execute_contract(&mut app, &pair.contract_addr, &PairExecuteMsg::AcceptOwnership {}, &[], ADDR3).unwrap_err();
+    execute_contract(&mut app, &pair.contract_addr, &PairExecuteMsg::AcceptOwnership {}, &[], ADDR2).unwrap();
+
+    let config: PairConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&pair.contract_addr, &PairQueryMsg::Config {})
+        .unwrap();
+    assert_eq!(
+        config,
+        PairConfigResponse {
+            owner: Addr::unchecked(ADDR2),
+            pending_owner: None,
+            protocol_fee_recipient: Addr::unchecked(ADDR3),
+            protocol_fee_percent: Decimal::from_str("0.1").unwrap(),
+            lp_fee_percent: Decimal::from_str("0.25").unwrap(),
+        }
+    );
+}
+
+fn router_test() {
+    let mut app = init_app(ADDR1);
+    let token_code_id = app.store_code(mock_cw20_contract());
+    let pair_code_id = app.store_code(mock_pair_contract());
+    let factory_code_id = app.store_code(mock_factory_contract());
+    let router_code_id = app.store_code(mock_router_contract());
+
+    let factory_addr = instantiate_factory_contract(
+        &mut app,
+        factory_code_id,
+        pair_code_id,
+        token_code_id,
+        ADDR1,
+        ADDR1,
+        "factory",
+    );
+    let router_addr =
+        instantiate_router_contract(&mut app, router_code_id, &factory_addr, ADDR1, ADDR1, "router");
+
+    let ukrw = AssetInfo::NativeToken {
+        denom: "ukrw".to_string(),
+    };
+
+    // channel token A / ukrw and channel token B / ukrw pairs with the same liquidity
+    let mut channel_token_addrs = vec![];
+    let mut pair_addrs = vec![];
+    for symbol in ["channel-a", "channel-b"] {
+        let token_addr = instantiate_cw20_contract(
+            &mut app,
+            token_code_id,
+            &[],
+            ADDR1,
+            ADDR1,
+            symbol,
+            symbol,
+            vec![],
+            symbol,
+        );
+        execute_mint(&mut app, &token_addr, ADDR1, ADDR1, 1_100_000);
+
+        let token = AssetInfo::Token {
+            contract_addr: token_addr.clone(),
+        };
+        execute_create_pair(&mut app, &factory_addr, [token.clone(), ukrw.clone()], ADDR1).unwrap();
+        let pair: PairInfo = app
+            .wrap()
+            .query_wasm_smart(
+                &factory_addr,
+                &FactoryQueryMsg::Pair {
+                    asset_infos: [token, ukrw.clone()],
+                },
+            )
+            .unwrap();
+
+        increase_allowance(&mut app, ADDR1, &pair.contract_addr, &token_addr, 1_000_000);
+        execute_provide_liquidity(
+            &mut app,
+            "ukrw",
+            1_000_000,
+            &token_addr,
+            1_000_000,
+            &pair.contract_addr,
+            ADDR1,
+        );
+        channel_token_addrs.push(token_addr);
+        pair_addrs.push(pair.contract_addr);
+    }
+
+    let channel_a = AssetInfo::Token {
+        contract_addr: channel_token_addrs[0].clone(),
+    };
+    let channel_b = AssetInfo::Token {
+        contract_addr: channel_token_addrs[1].clone(),
+    };
+    let operations = vec![
+        SwapOperation {
+            offer_asset_info: channel_a,
+            ask_asset_info: ukrw.clone(),
+        },
+        SwapOperation {
+            offer_asset_info: ukrw,
+            ask_asset_info: channel_b,
+        },
+    ];
+
+    let simulation: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router_addr,
+            &RouterQueryMsg::SimulateSwapOperations {
+                offer_amount: Uint128::new(10000),
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
+    println!("simulated channel-a -> ukrw -> channel-b: {:?}", simulation);
+    assert!(!simulation.amount.is_zero());
+
+    // the whole route is reverted if less than minimum_receive arrives
+    execute_swap_operations(
+        &mut app,
+        &router_addr,
+        &channel_token_addrs[0],
+        operations.clone(),
+        simulation.amount.u128() + 1,
+        ADDR1,
+        10000,
+    )
+    .unwrap_err();
+    assert_eq!(
+        query_cw20_balance(&app, &channel_token_addrs[0], ADDR1),
+        Uint128::new(100_000)
+    );
+
+    execute_swap_operations(
+        &mut app,
+        &router_addr,
+        &channel_token_addrs[0],
+        operations,
+        simulation.amount.u128(),
+        ADDR1,
+        10000,
+    )
+    .unwrap();
+    assert_eq!(
+        query_cw20_balance(&app, &channel_token_addrs[0], ADDR1),
+        Uint128::new(90_000)
+    );
+    assert_eq!(
+        query_cw20_balance(&app, &channel_token_addrs[1], ADDR1),
+        Uint128::new(100_000) + simulation.amount
+    );
+
+    // native routes swap only the sent coin, a stray router balance is left untouched
+    let native_operations = vec![SwapOperation {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: channel_token_addrs[1].clone(),
+        },
+    }];
+    let native_simulation: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router_addr,
+            &RouterQueryMsg::SimulateSwapOperations {
+                offer_amount: Uint128::new(10000),
+                operations: native_operations.clone(),
+            },
+        )
+        .unwrap();
+    app.send_tokens(Addr::unchecked(ADDR1), router_addr.clone(), &coins(5000, "ukrw")).unwrap();
+    let native_swap_msg = RouterExecuteMsg::ExecuteSwapOperations {
+        operations: native_operations,
+        minimum_receive: native_simulation.amount,
+        to: None,
+    };
+    execute_contract(&mut app, &router_addr, &native_swap_msg, &[], ADDR1).unwrap_err();
+    execute_contract(&mut app, &router_addr, &native_swap_msg, &coins(10000, "uusd"), ADDR1).unwrap_err();
+
+    let channel_b_balance = query_cw20_balance(&app, &channel_token_addrs[1], ADDR1);
+    execute_contract(&mut app, &router_addr, &native_swap_msg, &coins(10000, "ukrw"), ADDR1).unwrap();
+    assert_eq!(
+        query_cw20_balance(&app, &channel_token_addrs[1], ADDR1),
+        channel_b_balance + native_simulation.amount
+    );
+    assert_eq!(
+        app.wrap().query_balance(&router_addr, "ukrw").unwrap().amount,
+        Uint128::new(5000)
+    );
+
+    // protocol fees accrue in the offered asset of each pair, the channel token included
+    let accrued: AccruedProtocolFeesResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_addrs[0], &PairQueryMsg::AccruedProtocolFees {})
+        .unwrap();
+    println!("accrued protocol fees: {:?}", accrued);
+    assert_eq!(accrued.fees[0].amount, Uint128::new(15));
+    assert!(accrued.fees[1].amount.is_zero());
+
+    // only the protocol fee recipient can collect
+    execute_contract(&mut app, &pair_addrs[0], &PairExecuteMsg::CollectProtocolFees {}, &[], ADDR2).unwrap_err();
+    execute_contract(&mut app, &pair_addrs[0], &PairExecuteMsg::CollectProtocolFees {}, &[], ADDR1).unwrap();
+    assert_eq!(
+        query_cw20_balance(&app, &channel_token_addrs[0], ADDR1),
+        Uint128::new(90_015)
+    );
+
+    let accrued: AccruedProtocolFeesResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_addrs[0], &PairQueryMsg::AccruedProtocolFees {})
+        .unwrap();
+    assert!(accrued.fees.iter().all(|fee| fee.amount.is_zero()));
+
+    // a swap moving the price by several percent exceeds the default max spread
+    let swap_msg = |max_spread: Option<&str>| Cw20ExecuteMsg::Send {
+        contract: pair_addrs[0].to_string(),
+        amount: Uint128::new(50_000),
+        msg: to_binary(&PairCw20HookMsg::Swap {
+            min_output_amount: None,
+            belief_price: None,
+            max_spread: max_spread.map(String::from),
+            to: None,
+        })
+        .unwrap(),
+    };
+    execute_contract(&mut app, &channel_token_addrs[0], &swap_msg(None), &[], ADDR1).unwrap_err();
+    execute_contract(&mut app, &channel_token_addrs[0], &swap_msg(Some("10")), &[], ADDR1).unwrap();
+
+    // tokens sent directly to the pair are not part of the reserves
+    let transfer_msg = Cw20ExecuteMsg::Transfer {
+        recipient: pair_addrs[0].to_string(),
+        amount: Uint128::new(1000),
+    };
+    let query_drift = |app: &BasicApp| -> ReserveDriftResponse {
+        app.wrap()
+            .query_wasm_smart(&pair_addrs[0], &PairQueryMsg::ReserveDrift {})
+            .unwrap()
+    };
+
+    execute_contract(&mut app, &channel_token_addrs[0], &transfer_msg, &[], ADDR1).unwrap();
+    let drift = query_drift(&app);
+    println!("reserve drift: {:?}", drift);
+    assert_eq!(drift.assets[0].excess, Uint128::new(1000));
+    assert!(drift.assets[1].excess.is_zero());
+
+    // only the owner can skim the excess
+    let skim_msg = PairExecuteMsg::Skim {
+        to: ADDR3.to_string(),
+    };
+    execute_contract(&mut app, &pair_addrs[0], &skim_msg, &[], ADDR2).unwrap_err();
+    execute_contract(&mut app, &pair_addrs[0], &skim_msg, &[], ADDR1).unwrap();
+    assert_eq!(
+        query_cw20_balance(&app, &channel_token_addrs[0], ADDR3),
+        Uint128::new(1000)
+    );
+    assert!(query_drift(&app).assets[0].excess.is_zero());
+
+    // sync adopts the excess as reserve
+    execute_contract(&mut app, &channel_token_addrs[0], &transfer_msg, &[], ADDR1).unwrap();
+    let reserve_before_sync = query_drift(&app).assets[0].reserve;
+    execute_contract(&mut app, &pair_addrs[0], &PairExecuteMsg::Sync {}, &[], ADDR2).unwrap();
+    let drift = query_drift(&app);
+    assert_eq!(drift.assets[0].reserve, reserve_before_sync + Uint128::new(1000));
+    assert!(drift.assets.iter().all(|asset| asset.excess.is_zero() && asset.deficit.is_zero()));
+}
+
+fn inflation_attack_test() {
+    let mut app = init_app(ADDR1);
+    let (token_addr, pair_addr, lp_token_addr) = setup_pair(&mut app);
+
+    // ADDR2 is the attacker, ADDR1 the victim
+    execute_mint(&mut app, &token_addr, ADDR1, ADDR1, 1_000_000);
+    execute_mint(&mut app, &token_addr, ADDR1, ADDR2, 1_000_000);
+    app.send_tokens(
+        Addr::unchecked(ADDR1),
+        Addr::unchecked(ADDR2),
+        &[coin(1_000_000, "ukrw")],
+    )
+    .unwrap();
+    increase_allowance(&mut app, ADDR1, &pair_addr, &token_addr, 1_000_000);
+    increase_allowance(&mut app, ADDR2, &pair_addr, &token_addr, 1_000_000);
+
+    // a dust first deposit is rejected
+    let provide_msg = |amount: u128| PairExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: token_addr.clone(),
+                },
+                amount: Uint128::new(amount),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "ukrw".to_string(),
+                },
+                amount: Uint128::new(amount),
+            },
+        ],
+        slippage_tolerance: None,
+    };
+    execute_contract(&mut app, &pair_addr, &provide_msg(1), &[coin(1, "ukrw")], ADDR2).unwrap_err();
+
+    // the smallest accepted first deposit only gets a single lp token, the rest is locked
+    execute_contract(&mut app, &pair_addr, &provide_msg(1001), &[coin(1001, "ukrw")], ADDR2).unwrap();
+    assert_eq!(query_cw20_balance(&app, &lp_token_addr, ADDR2), Uint128::new(1));
+    assert_eq!(query_cw20_balance(&app, &lp_token_addr, pair_addr.as_str()), Uint128::new(1000));
+
+    // the attacker inflates the lp token price with a donation
+    execute_contract(
+        &mut app,
+        &token_addr,
+        &Cw20ExecuteMsg::Transfer {
+            recipient: pair_addr.to_string(),
+            amount: Uint128::new(100_000),
+        },
+        &[],
+        ADDR2,
+    )
+    .unwrap();
+    app.send_tokens(
+        Addr::unchecked(ADDR2),
+        pair_addr.clone(),
+        &[coin(100_000, "ukrw")],
+    )
+    .unwrap();
+    execute_contract(&mut app, &pair_addr, &PairExecuteMsg::Sync {}, &[], ADDR2).unwrap();
+
+    execute_contract(&mut app, &pair_addr, &provide_msg(50_000), &[coin(50_000, "ukrw")], ADDR1).unwrap();
+    let victim_lp = query_cw20_balance(&app, &lp_token_addr, ADDR1);
+    assert!(!victim_lp.is_zero());
+
+    // both withdraw everything they can
+    execute_withdraw_liquidity(&mut app, &pair_addr, &lp_token_addr, ADDR2, Uint128::new(1));
+    execute_withdraw_liquidity(&mut app, &pair_addr, &lp_token_addr, ADDR1, victim_lp);
+
+    // the donation went to the locked liquidity and the victim lost less than 1%
+    let attacker_balance = query_cw20_balance(&app, &token_addr, ADDR2);
+    println!("attacker token balance after the attack: {}", attacker_balance);
+    assert!(attacker_balance < Uint128::new(1_000_000 - 100_000));
+
+    let victim_balance = query_cw20_balance(&app, &token_addr, ADDR1);
+    println!("victim token balance after the attack: {}", victim_balance);
+    assert!(victim_balance > Uint128::new(1_000_000 - 500));
+}
+
+fn provide_liquidity_test() {
+    let mut app = init_app(ADDR1);
+    let (token_addr, pair_addr, lp_token_addr) = setup_pair_with_liquidity(&mut app);
+
+    let token = |amount: u128| Asset {
+        info: AssetInfo::Token {
+            contract_addr: token_addr.clone(),
+        },
+        amount: Uint128::new(amount),
+    };
+    let ukrw = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+        amount: Uint128::new(amount),
+    };
+
+    // native asset first and the cw20 token in excess, only the needed token amount is pulled
+    let provide_msg = PairExecuteMsg::ProvideLiquidity {
+        assets: [ukrw(10_000), token(20_000)],
+        slippage_tolerance: None,
+    };
+    execute_contract(&mut app, &pair_addr, &provide_msg, &[coin(10_000, "ukrw")], ADDR1).unwrap();
+    assert_eq!(query_cw20_balance(&app, &token_addr, ADDR1), Uint128::new(890_000));
+    assert_eq!(query_cw20_balance(&app, &lp_token_addr, ADDR1), Uint128::new(109_000));
+
+    // native asset in excess is refunded
+    let ukrw_before = app.wrap().query_balance(ADDR1, "ukrw").unwrap().amount;
+    let provide_msg = PairExecuteMsg::ProvideLiquidity {
+        assets: [token(10_000), ukrw(20_000)],
+        slippage_tolerance: None,
+    };
+    execute_contract(&mut app, &pair_addr, &provide_msg, &[coin(20_000, "ukrw")], ADDR1).unwrap();
+    let ukrw_after = app.wrap().query_balance(ADDR1, "ukrw").unwrap().amount;
+    assert_eq!(ukrw_before - ukrw_after, Uint128::new(10_000));
+    assert_eq!(query_cw20_balance(&app, &lp_token_addr, ADDR1), Uint128::new(119_000));
+
+    // the deposit ratio is too far from the pool ratio
+    let provide_msg = PairExecuteMsg::ProvideLiquidity {
+        assets: [token(10_000), ukrw(12_000)],
+        slippage_tolerance: Some("1".to_string()),
+    };
+    execute_contract(&mut app, &pair_addr, &provide_msg, &[coin(12_000, "ukrw")], ADDR1).unwrap_err();
+}
+
+fn provide_single_sided_test() {
+    let mut app = init_app(ADDR1);
+    let (token_addr, pair_addr, lp_token_addr) = setup_pair(&mut app);
+
+    let ukrw = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+        amount: Uint128::new(amount),
+    };
+
+    // the pool ratio has to be set by a regular deposit first
+    let provide_msg = PairExecuteMsg::ProvideSingleSided { asset: ukrw(10_000), min_lp_out: None };
+    execute_contract(&mut app, &pair_addr, &provide_msg, &[coin(10_000, "ukrw")], ADDR1).unwrap_err();
+
+    provide_initial_liquidity(&mut app, &token_addr, &pair_addr);
+
+    let simulation: SimulateProvideSingleSidedResponse = app.wrap().query_wasm_smart(
+        pair_addr.clone(),
+        &PairQueryMsg::SimulateProvideSingleSided { asset: ukrw(10_000) },
+    ).unwrap();
+    // a bit less than half is swapped, the price moves against the swap
+    assert!(simulation.swap_amount < Uint128::new(5_000));
+    assert!(simulation.lp_amount > Uint128::new(4_500));
+
+    // without min_lp_out the internal swap of about 5% of the reserve exceeds the default max spread
+    let provide_msg = PairExecuteMsg::ProvideSingleSided { asset: ukrw(10_000), min_lp_out: None };
+    execute_contract(&mut app, &pair_addr, &provide_msg, &[coin(10_000, "ukrw")], ADDR1).unwrap_err();
+
+    let provide_msg = PairExecuteMsg::ProvideSingleSided {
+        asset: ukrw(10_000),
+        min_lp_out: Some(simulation.lp_amount + Uint128::new(1)),
+    };
+    execute_contract(&mut app, &pair_addr, &provide_msg, &[coin(10_000, "ukrw")], ADDR1).unwrap_err();
+
+    let token_before = query_cw20_balance(&app, &token_addr, ADDR1);
+    let provide_msg = PairExecuteMsg::ProvideSingleSided {
+        asset: ukrw(10_000),
+        min_lp_out: Some(simulation.lp_amount),
+    };
+    execute_contract(&mut app, &pair_addr, &provide_msg, &[coin(10_000, "ukrw")], ADDR1).unwrap();
+    assert_eq!(
+        query_cw20_balance(&app, &lp_token_addr, ADDR1),
+        Uint128::new(99_000) + simulation.lp_amount
+    );
+    // only the dust of the swapped side is refunded
+    let token_refund = query_cw20_balance(&app, &token_addr, ADDR1) - token_before;
+    let token_info = AssetInfo::Token { contract_addr: token_addr.clone() };
+    let simulated_refund = simulation.refund_assets.iter().find(|a| a.info == token_info).unwrap();
+    assert_eq!(token_refund, simulated_refund.amount);
+
+    // the reserves still match the balances of the pair
+    assert_no_drift(&app, &pair_addr);
+
+    // cw20 tokens are provided through the Receive hook only
+    let token = Asset { info: AssetInfo::Token { contract_addr: token_addr.clone() }, amount: Uint128::new(10_000) };
+    let provide_msg = PairExecuteMsg::ProvideSingleSided { asset: token, min_lp_out: None };
+    execute_contract(&mut app, &pair_addr, &provide_msg, &[], ADDR1).unwrap_err();
+
+    let lp_before = query_cw20_balance(&app, &lp_token_addr, ADDR1);
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: pair_addr.to_string(),
+        amount: Uint128::new(10_000),
+        msg: to_binary(&PairCw20HookMsg::ProvideSingleSided { min_lp_out: Some(Uint128::new(4_500)) }).unwrap(),
+    };
+    execute_contract(&mut app, &token_addr, &send_msg, &[], ADDR1).unwrap();
+    assert!(query_cw20_balance(&app, &lp_token_addr, ADDR1) - lp_before > Uint128::new(4_500));
+
+    assert_no_drift(&app, &pair_addr);
+}
+
+fn withdraw_single_sided_test() {
+    let mut app = init_app(ADDR1);
+    let (token_addr, pair_addr, lp_token_addr) = setup_pair_with_liquidity(&mut app);
+    let ukrw_info = AssetInfo::NativeToken { denom: "ukrw".to_string() };
+
+    increase_allowance(&mut app, ADDR1, &pair_addr, &lp_token_addr, 99_000);
+
+    // min_out needs the asset it applies to
+    let withdraw_msg = PairExecuteMsg::WithdrawLiquidity {
+        amount: Uint128::new(10_000),
+        receive_asset: None,
+        min_out: Some(Uint128::new(1)),
+    };
+    execute_contract(&mut app, &pair_addr, &withdraw_msg, &[], ADDR1).unwrap_err();
+
+    // 10_000 of each side is withdrawn, the channel token side swapped for about 9_000 ukrw
+    let withdraw_msg = PairExecuteMsg::WithdrawLiquidity {
+        amount: Uint128::new(10_000),
+        receive_asset: Some(ukrw_info.clone()),
+        min_out: Some(Uint128::new(19_500)),
+    };
+    execute_contract(&mut app, &pair_addr, &withdraw_msg, &[], ADDR1).unwrap_err();
+
+    let token_before = query_cw20_balance(&app, &token_addr, ADDR1);
+    let ukrw_before = app.wrap().query_balance(ADDR1, "ukrw").unwrap().amount;
+    let withdraw_msg = PairExecuteMsg::WithdrawLiquidity {
+        amount: Uint128::new(10_000),
+        receive_asset: Some(ukrw_info.clone()),
+        min_out: Some(Uint128::new(18_000)),
+    };
+    let attrs = execute_contract(&mut app, &pair_addr, &withdraw_msg, &[], ADDR1).unwrap();
+    let attr = |key: &str| attrs.iter().find(|a| a.key == key).unwrap().value.clone();
+    assert_eq!(attr("swap_offer_amount"), "10000");
+    let return_amount = Uint128::from_str(&attr("return_amount")).unwrap();
+    assert_eq!(
+        return_amount,
+        Uint128::new(10_000) + Uint128::from_str(&attr("swap_return_amount")).unwrap()
+    );
+
+    let ukrw_after = app.wrap().query_balance(ADDR1, "ukrw").unwrap().amount;
+    assert_eq!(ukrw_after - ukrw_before, return_amount);
+    assert_eq!(query_cw20_balance(&app, &token_addr, ADDR1), token_before);
+    assert_eq!(query_cw20_balance(&app, &lp_token_addr, ADDR1), Uint128::new(89_000));
+
+    // the internal swap accrues the protocol fee like a regular swap
+    let fees: AccruedProtocolFeesResponse = app.wrap().query_wasm_smart(pair_addr.clone(), &PairQueryMsg::AccruedProtocolFees {}).unwrap();
+    let token_fee = fees.fees.iter().find(|a| a.info != ukrw_info).unwrap();
+    assert_eq!(token_fee.amount.to_string(), attr("protocol_fee_amount"));
+
+    // the channel token through the Receive hook of the LP token
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: pair_addr.to_string(),
+        amount: Uint128::new(10_000),
+        msg: to_binary(&PairCw20HookMsg::WithdrawLiquidity {
+            receive_asset: Some(AssetInfo::Token { contract_addr: token_addr.clone() }),
+            min_out: Some(Uint128::new(18_000)),
+        }).unwrap(),
+    };
+    execute_contract(&mut app, &lp_token_addr, &send_msg, &[], ADDR1).unwrap();
+    assert!(query_cw20_balance(&app, &token_addr, ADDR1) - token_before > Uint128::new(18_000));
+    assert_eq!(app.wrap().query_balance(ADDR1, "ukrw").unwrap().amount, ukrw_after);
+
+    // a large swap skews the pool, like a front-run of the withdrawal
+    let swap_msg = Cw20ExecuteMsg::Send {
+        contract: pair_addr.to_string(),
+        amount: Uint128::new(30_000),
+        msg: to_binary(&PairCw20HookMsg::Swap {
+            min_output_amount: None,
+            belief_price: None,
+            max_spread: Some("50".to_string()),
+            to: None,
+        }).unwrap(),
+    };
+    execute_contract(&mut app, &token_addr, &swap_msg, &[], ADDR1).unwrap();
+
+    // without min_out the internal swap is bounded by the default max spread of a swap
+    let withdraw_msg = |amount: u128, min_out: Option<u128>| PairExecuteMsg::WithdrawLiquidity {
+        amount: Uint128::new(amount),
+        receive_asset: Some(ukrw_info.clone()),
+        min_out: min_out.map(Uint128::new),
+    };
+    execute_contract(&mut app, &pair_addr, &withdraw_msg(5_000, None), &[], ADDR1).unwrap_err();
+    execute_contract(&mut app, &pair_addr, &withdraw_msg(5_000, Some(1)), &[], ADDR1).unwrap();
+    execute_contract(&mut app, &pair_addr, &withdraw_msg(200, None), &[], ADDR1).unwrap();
+
+    assert_no_drift(&app, &pair_addr);
+}
+
+fn stable_pair_test() {
+    let mut app = init_app(ADDR1);
+    let token_code_id = app.store_code(mock_cw20_contract());
+    let pair_code_id = app.store_code(mock_pair_contract());
+    let factory_code_id = app.store_code(mock_factory_contract());
+
+    let factory_addr = instantiate_factory_contract(
+        &mut app,
+        factory_code_id,
+        pair_code_id,
+        token_code_id,
+        ADDR1,
+        ADDR1,
+        "factory",
+    );
+    let token_addr = instantiate_cw20_contract(
+        &mut app,
+        token_code_id,
+        &[],
+        ADDR1,
+        ADDR1,
+        "krw_stable",
+        "krw-stable",
+        vec![],
+        "krw_stable",
+    );
+    let asset_infos = [
+        AssetInfo::Token { contract_addr: token_addr.clone() },
+        AssetInfo::NativeToken { denom: "ukrw".to_string() },
+    ];
+
+    // a stable pair needs an amplification
+    let create_pair_msg = FactoryExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        pair_type: Some(PairType::Stable {}),
+        amp: None,
+    };
+    execute_contract(&mut app, &factory_addr, &create_pair_msg, &[], ADDR1).unwrap_err();
+
+    let create_pair_msg = FactoryExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        pair_type: Some(PairType::Stable {}),
+        amp: Some(100),
+    };
+    // only the factory owner can create stable pairs
+    execute_contract(&mut app, &factory_addr, &create_pair_msg, &[], ADDR2).unwrap_err();
+    execute_contract(&mut app, &factory_addr, &create_pair_msg, &[], ADDR1).unwrap();
+    let pair: PairInfo = app.wrap().query_wasm_smart(
+        factory_addr.clone(),
+        &FactoryQueryMsg::Pair { asset_infos: asset_infos.clone() },
+    ).unwrap();
+    assert_eq!(pair.pair_type, PairType::Stable {});
+    assert_eq!(query_pair_info(&app, &pair.contract_addr).pair_type, PairType::Stable {});
+    let pair_addr = pair.contract_addr;
+
+    provide_initial_liquidity(&mut app, &token_addr, &pair_addr);
+
+    // close to the peg less the fees, the constant product curve returns about 9_050
+    let ukrw = Asset { info: asset_infos[1].clone(), amount: Uint128::new(10_000) };
+    let simulation: SimulationResponse = app.wrap().query_wasm_smart(
+        pair_addr.clone(),
+        &PairQueryMsg::Simulation { offer_asset: ukrw.clone() },
+    ).unwrap();
+    assert!(simulation.return_amount > Uint128::new(9_900));
+    println!("stable pair simulation: {:?}", simulation);
+
+    let token_before = query_cw20_balance(&app, &token_addr, ADDR1);
+    let swap_msg = PairExecuteMsg::Swap {
+        offer_asset: ukrw,
+        min_output_amount: Some(simulation.return_amount.to_string()),
+        belief_price: None,
+        max_spread: None,
+        to: None,
+    };
+    execute_contract(&mut app, &pair_addr, &swap_msg, &[coin(10_000, "ukrw")], ADDR1).unwrap();
+    assert_eq!(query_cw20_balance(&app, &token_addr, ADDR1) - token_before, simulation.return_amount);
+
+    let amp: AmpResponse = app.wrap().query_wasm_smart(pair_addr.clone(), &PairQueryMsg::Amp {}).unwrap();
+    assert_eq!(amp.amp, 100);
+
+    // the amplification can not change right after it was set
+    let now = app.block_info().time.seconds();
+    let change_amp_msg = PairExecuteMsg::StartChangingAmp { next_amp: 200, next_amp_time: now + 2 * 86_400 };
+    execute_contract(&mut app, &pair_addr, &change_amp_msg, &[], ADDR1).unwrap_err();
+
+    app.update_block(|block| block.time = block.time.plus_seconds(86_400));
+    let now = app.block_info().time.seconds();
+
+    // too fast, too far and not by the owner
+    let change_amp_msg = PairExecuteMsg::StartChangingAmp { next_amp: 200, next_amp_time: now + 3_600 };
+    execute_contract(&mut app, &pair_addr, &change_amp_msg, &[], ADDR1).unwrap_err();
+    let change_amp_msg = PairExecuteMsg::StartChangingAmp { next_amp: 2_000, next_amp_time: now + 2 * 86_400 };
+    execute_contract(&mut app, &pair_addr, &change_amp_msg, &[], ADDR1).unwrap_err();
+    let change_amp_msg = PairExecuteMsg::StartChangingAmp { next_amp: 200, next_amp_time: now + 2 * 86_400 };
+    execute_contract(&mut app, &pair_addr, &change_amp_msg, &[], ADDR2).unwrap_err();
+
+    execute_contract(&mut app, &pair_addr, &change_amp_msg, &[], ADDR1).unwrap();
+
+    // half way through the ramp
+    app.update_block(|block| block.time = block.time.plus_seconds(86_400));
+    let amp: AmpResponse = app.wrap().query_wasm_smart(pair_addr.clone(), &PairQueryMsg::Amp {}).unwrap();
+    assert_eq!(amp.amp, 150);
+
+    execute_contract(&mut app, &pair_addr, &PairExecuteMsg::StopChangingAmp {}, &[], ADDR1).unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(86_400));
+    let amp: AmpResponse = app.wrap().query_wasm_smart(pair_addr.clone(), &PairQueryMsg::Amp {}).unwrap();
+    assert_eq!(amp.amp, 150);
+    assert_eq!(amp.next_amp, 150);
+
+    // the oracle follows the stable curve, the reserve ratio after the swap is about 1.22
+    let twap: TwapResponse = app.wrap().query_wasm_smart(
+        pair_addr.clone(),
+        &PairQueryMsg::Twap { window_seconds: 86_400 },
+    ).unwrap();
+    println!("stable pair twap: {:?}", twap);
+    assert!(twap.price0_average > Decimal::one());
+    assert!(twap.price0_average < Decimal::percent(101));
+    assert!(twap.price1_average < Decimal::one());
+}
+
 fn main() {
     // basic_test()
     ico_test();
+    ico_pool_test();
+    ico_vesting_test();
+    ico_channel_token_policy_test();
+    factory_test();
+    router_test();
+    inflation_attack_test();
+    provide_liquidity_test();
+    provide_single_sided_test();
+    withdraw_single_sided_test();
+    stable_pair_test();
 }
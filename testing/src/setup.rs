@@ -0,0 +1,76 @@
+use crate::execute::{execute_mint, execute_provide_liquidity, increase_allowance};
+use crate::init::{mock_cw20_contract, mock_pair_contract};
+use crate::instantiate::{instantiate_cw20_contract, instantiate_pair_contract};
+use crate::query::{query_cw20_balance, query_pair_info};
+use cosmwasm_std::{Addr, Uint128};
+use cw_multi_test::BasicApp;
+use testing_base::consts::ADDR1;
+use ysip::pair::{QueryMsg, ReserveDriftResponse};
+
+/// Channel token / ukrw pair without liquidity, owned by ADDR1.
+/// Returns the channel token, the pair and the lp token
+pub fn setup_pair(app: &mut BasicApp) -> (Addr, Addr, Addr) {
+    let token_code_id = app.store_code(mock_cw20_contract());
+    let pair_code_id = app.store_code(mock_pair_contract());
+
+    let token_addr = instantiate_cw20_contract(
+        app,
+        token_code_id,
+        &[],
+        ADDR1,
+        ADDR1,
+        "channel_a",
+        "channel-a",
+        vec![],
+        "channel_a",
+    );
+    let pair_addr = instantiate_pair_contract(
+        app,
+        pair_code_id,
+        token_code_id,
+        &[],
+        ADDR1,
+        ADDR1,
+        &token_addr,
+        "ukrw",
+        "pair",
+    );
+    let lp_token_addr = query_pair_info(app, &pair_addr).liquidity_token;
+
+    (token_addr, pair_addr, lp_token_addr)
+}
+
+/// Mints 1_000_000 channel tokens to ADDR1 and provides 100_000 of each side as the first deposit
+pub fn provide_initial_liquidity(app: &mut BasicApp, token_addr: &Addr, pair_addr: &Addr) {
+    execute_mint(app, token_addr, ADDR1, ADDR1, 1_000_000);
+    increase_allowance(app, ADDR1, pair_addr, token_addr, 1_000_000);
+    execute_provide_liquidity(app, "ukrw", 100_000, token_addr, 100_000, pair_addr, ADDR1);
+
+    // the minimum liquidity stays locked in the pair
+    let lp_token_addr = query_pair_info(app, pair_addr).liquidity_token;
+    assert_eq!(
+        query_cw20_balance(app, &lp_token_addr, ADDR1),
+        Uint128::new(99_000)
+    );
+}
+
+/// [`setup_pair`] with [`provide_initial_liquidity`]
+pub fn setup_pair_with_liquidity(app: &mut BasicApp) -> (Addr, Addr, Addr) {
+    let (token_addr, pair_addr, lp_token_addr) = setup_pair(app);
+    provide_initial_liquidity(app, &token_addr, &pair_addr);
+
+    (token_addr, pair_addr, lp_token_addr)
+}
+
+/// The reserves of the pair match its balances
+pub fn assert_no_drift(app: &BasicApp, pair_addr: &Addr) {
+    let drift: ReserveDriftResponse = app
+        .wrap()
+        .query_wasm_smart(pair_addr, &QueryMsg::ReserveDrift {})
+        .unwrap();
+
+    for asset in drift.assets {
+        assert!(asset.excess.is_zero());
+        assert!(asset.deficit.is_zero());
+    }
+}